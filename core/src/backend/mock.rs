@@ -0,0 +1,384 @@
+//! A scriptable in-memory backend, for tests.
+
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    backend::{
+        AutoRepeatMode, EventHandler, EventRegistrationToken, HandlerList, MediaProperties,
+        MediaSession, MediaSessionManager, PlaybackControls, PlaybackInfo, PlaybackStatus,
+        TimelineProperties,
+    },
+    types::ArtData,
+};
+
+/// A command a [`MockSession`] received, in the order `Player` issued them.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockCommand {
+    Play,
+    Pause,
+    TogglePlayPause,
+    Stop,
+    SkipNext,
+    SkipPrevious,
+    ChangeShuffleActive(bool),
+    ChangeAutoRepeatMode(AutoRepeatMode),
+    ChangePlaybackPosition(i64),
+}
+
+struct MockState {
+    playback_info: Option<PlaybackInfo>,
+    timeline_properties: Option<TimelineProperties>,
+    media_properties: Option<MediaProperties>,
+    thumbnail: Option<ArtData>,
+
+    command_result: bool,
+    commands: Vec<MockCommand>,
+}
+
+struct MockSessionInner {
+    aumid: String,
+    state: Mutex<MockState>,
+
+    playback_info_changed: Mutex<HandlerList>,
+    media_properties_changed: Mutex<HandlerList>,
+    timeline_properties_changed: Mutex<HandlerList>,
+}
+
+/// An in-memory session whose state is set by hand and whose events only
+/// fire when told to.
+///
+/// Clones share the same state, so a test can keep one around after handing
+/// another to a [`MockSessionManager`].
+#[derive(Clone)]
+pub struct MockSession {
+    inner: Arc<MockSessionInner>,
+}
+
+impl MockSession {
+    /// A stopped session with no timeline nor metadata.
+    pub fn new(aumid: &str) -> Self {
+        MockSession {
+            inner: Arc::new(MockSessionInner {
+                aumid: aumid.to_string(),
+                state: Mutex::new(MockState {
+                    playback_info: Some(PlaybackInfo {
+                        playback_status: Some(PlaybackStatus::Stopped),
+                        auto_repeat_mode: Some(AutoRepeatMode::None),
+                        is_shuffle_active: Some(false),
+                        controls: Some(PlaybackControls::default()),
+                    }),
+                    timeline_properties: None,
+                    media_properties: None,
+                    thumbnail: None,
+
+                    command_result: true,
+                    commands: Vec::new(),
+                }),
+
+                playback_info_changed: Mutex::new(HandlerList::default()),
+                media_properties_changed: Mutex::new(HandlerList::default()),
+                timeline_properties_changed: Mutex::new(HandlerList::default()),
+            }),
+        }
+    }
+
+    /// Replaces the playback info; no event is fired.
+    pub fn set_playback_info(&self, playback_info: Option<PlaybackInfo>) {
+        self.inner.state.lock().unwrap().playback_info = playback_info;
+    }
+
+    /// Changes only the playback status; no event is fired.
+    pub fn set_playback_status(&self, playback_status: PlaybackStatus) {
+        let mut state = self.inner.state.lock().unwrap();
+        if let Some(playback_info) = state.playback_info.as_mut() {
+            playback_info.playback_status = Some(playback_status);
+        }
+    }
+
+    /// Replaces the timeline; no event is fired.
+    pub fn set_timeline_properties(&self, timeline_properties: Option<TimelineProperties>) {
+        self.inner.state.lock().unwrap().timeline_properties = timeline_properties;
+    }
+
+    /// Replaces the metadata; no event is fired.
+    pub fn set_media_properties(&self, media_properties: Option<MediaProperties>) {
+        self.inner.state.lock().unwrap().media_properties = media_properties;
+    }
+
+    /// Replaces the cover art; no event is fired.
+    pub fn set_thumbnail(&self, thumbnail: Option<ArtData>) {
+        self.inner.state.lock().unwrap().thumbnail = thumbnail;
+    }
+
+    /// What every `Try*Async` lookalike returns from now on.
+    pub fn set_command_result(&self, result: bool) {
+        self.inner.state.lock().unwrap().command_result = result;
+    }
+
+    /// Runs the `PlaybackInfoChanged` handlers.
+    pub fn fire_playback_info_changed(&self) {
+        HandlerList::fire(&self.inner.playback_info_changed);
+    }
+
+    /// Runs the `MediaPropertiesChanged` handlers.
+    pub fn fire_media_properties_changed(&self) {
+        HandlerList::fire(&self.inner.media_properties_changed);
+    }
+
+    /// Runs the `TimelinePropertiesChanged` handlers.
+    pub fn fire_timeline_properties_changed(&self) {
+        HandlerList::fire(&self.inner.timeline_properties_changed);
+    }
+
+    /// The commands received so far.
+    pub fn commands(&self) -> Vec<MockCommand> {
+        self.inner.state.lock().unwrap().commands.clone()
+    }
+
+    /// Forgets the commands received so far.
+    pub fn clear_commands(&self) {
+        self.inner.state.lock().unwrap().commands.clear();
+    }
+
+    /// How many handlers are currently registered, across all three events.
+    pub fn handler_count(&self) -> usize {
+        self.inner.playback_info_changed.lock().unwrap().len()
+            + self.inner.media_properties_changed.lock().unwrap().len()
+            + self.inner.timeline_properties_changed.lock().unwrap().len()
+    }
+
+    fn command(&self, command: MockCommand) -> bool {
+        let mut state = self.inner.state.lock().unwrap();
+        state.commands.push(command);
+        state.command_result
+    }
+}
+
+impl PartialEq for MockSession {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl MediaSession for MockSession {
+    fn source_app_user_model_id(&self) -> Option<String> {
+        Some(self.inner.aumid.clone())
+    }
+
+    fn playback_info(&self) -> Option<PlaybackInfo> {
+        self.inner.state.lock().unwrap().playback_info.clone()
+    }
+
+    fn timeline_properties(&self) -> Option<TimelineProperties> {
+        self.inner.state.lock().unwrap().timeline_properties.clone()
+    }
+
+    fn media_properties(&self) -> Option<MediaProperties> {
+        self.inner.state.lock().unwrap().media_properties.clone()
+    }
+
+    fn thumbnail(&self) -> Option<ArtData> {
+        self.inner.state.lock().unwrap().thumbnail.clone()
+    }
+
+    async fn try_play(&self) -> bool {
+        self.command(MockCommand::Play)
+    }
+
+    async fn try_pause(&self) -> bool {
+        self.command(MockCommand::Pause)
+    }
+
+    async fn try_toggle_play_pause(&self) -> bool {
+        self.command(MockCommand::TogglePlayPause)
+    }
+
+    async fn try_stop(&self) -> bool {
+        self.command(MockCommand::Stop)
+    }
+
+    async fn try_skip_next(&self) -> bool {
+        self.command(MockCommand::SkipNext)
+    }
+
+    async fn try_skip_previous(&self) -> bool {
+        self.command(MockCommand::SkipPrevious)
+    }
+
+    async fn try_change_shuffle_active(&self, value: bool) -> bool {
+        self.command(MockCommand::ChangeShuffleActive(value))
+    }
+
+    async fn try_change_auto_repeat_mode(&self, value: AutoRepeatMode) -> bool {
+        self.command(MockCommand::ChangeAutoRepeatMode(value))
+    }
+
+    async fn try_change_playback_position(&self, position: i64) -> bool {
+        self.command(MockCommand::ChangePlaybackPosition(position))
+    }
+
+    fn playback_info_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.inner
+            .playback_info_changed
+            .lock()
+            .unwrap()
+            .add(handler)
+    }
+
+    fn media_properties_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.inner
+            .media_properties_changed
+            .lock()
+            .unwrap()
+            .add(handler)
+    }
+
+    fn timeline_properties_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.inner
+            .timeline_properties_changed
+            .lock()
+            .unwrap()
+            .add(handler)
+    }
+
+    fn remove_playback_info_changed(&self, token: EventRegistrationToken) {
+        self.inner
+            .playback_info_changed
+            .lock()
+            .unwrap()
+            .remove(token);
+    }
+
+    fn remove_media_properties_changed(&self, token: EventRegistrationToken) {
+        self.inner
+            .media_properties_changed
+            .lock()
+            .unwrap()
+            .remove(token);
+    }
+
+    fn remove_timeline_properties_changed(&self, token: EventRegistrationToken) {
+        self.inner
+            .timeline_properties_changed
+            .lock()
+            .unwrap()
+            .remove(token);
+    }
+}
+
+struct MockManagerInner {
+    sessions: Mutex<Vec<MockSession>>,
+    current_session: Mutex<Option<MockSession>>,
+
+    sessions_changed: Mutex<HandlerList>,
+    current_session_changed: Mutex<HandlerList>,
+}
+
+/// The manager side of the mock backend. Like [`MockSession`], clones share
+/// their state.
+#[derive(Clone)]
+pub struct MockSessionManager {
+    inner: Arc<MockManagerInner>,
+}
+
+impl Default for MockSessionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockSessionManager {
+    /// A manager without sessions.
+    pub fn new() -> Self {
+        MockSessionManager {
+            inner: Arc::new(MockManagerInner {
+                sessions: Mutex::new(Vec::new()),
+                current_session: Mutex::new(None),
+
+                sessions_changed: Mutex::new(HandlerList::default()),
+                current_session_changed: Mutex::new(HandlerList::default()),
+            }),
+        }
+    }
+
+    /// Registers a new session and returns a handle to script it with.
+    pub fn add_session(&self, aumid: &str) -> MockSession {
+        let session = MockSession::new(aumid);
+        self.inner.sessions.lock().unwrap().push(session.clone());
+        session
+    }
+
+    /// Unregisters the sessions of `aumid`.
+    pub fn remove_session(&self, aumid: &str) {
+        self.inner
+            .sessions
+            .lock()
+            .unwrap()
+            .retain(|x| x.inner.aumid != aumid);
+    }
+
+    /// Unregisters `session` alone, leaving any other session of its AUMID.
+    pub fn remove(&self, session: &MockSession) {
+        self.inner.sessions.lock().unwrap().retain(|x| x != session);
+    }
+
+    /// Sets what `get_current_session` returns.
+    pub fn set_current_session(&self, session: Option<&MockSession>) {
+        *self.inner.current_session.lock().unwrap() = session.cloned();
+    }
+
+    /// Runs the `SessionsChanged` handlers.
+    pub fn fire_sessions_changed(&self) {
+        HandlerList::fire(&self.inner.sessions_changed);
+    }
+
+    /// Runs the `CurrentSessionChanged` handlers.
+    pub fn fire_current_session_changed(&self) {
+        HandlerList::fire(&self.inner.current_session_changed);
+    }
+}
+
+impl MediaSessionManager for MockSessionManager {
+    type Session = MockSession;
+
+    async fn request() -> Option<Self> {
+        Some(Self::new())
+    }
+
+    fn get_sessions(&self) -> Option<Vec<Self::Session>> {
+        Some(self.inner.sessions.lock().unwrap().clone())
+    }
+
+    fn get_current_session(&self) -> Option<Self::Session> {
+        self.inner.current_session.lock().unwrap().clone()
+    }
+
+    async fn get_friendly_name(&self, aumid: &str) -> Option<String> {
+        Some(aumid.to_string())
+    }
+
+    fn sessions_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.inner.sessions_changed.lock().unwrap().add(handler)
+    }
+
+    fn current_session_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.inner
+            .current_session_changed
+            .lock()
+            .unwrap()
+            .add(handler)
+    }
+
+    fn remove_sessions_changed(&self, token: EventRegistrationToken) {
+        self.inner.sessions_changed.lock().unwrap().remove(token);
+    }
+
+    fn remove_current_session_changed(&self, token: EventRegistrationToken) {
+        self.inner
+            .current_session_changed
+            .lock()
+            .unwrap()
+            .remove(token);
+    }
+}
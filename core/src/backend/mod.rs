@@ -0,0 +1,192 @@
+//! The platform specific side of things.
+//!
+//! A backend provides a [`MediaSessionManager`] and its [`MediaSession`]s;
+//! [`Player`](crate::Player) and [`PlayerManager`](crate::PlayerManager) are
+//! generic over them.
+
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::types::ArtData;
+
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+#[cfg(feature = "mpris")]
+pub mod mpris;
+#[cfg(feature = "winrt")]
+pub mod winrt;
+
+/// Playback status of a session.
+// Mirrors GlobalSystemMediaTransportControlsSessionPlaybackStatus, so that the
+// Debug representation stays the same string we always handed out
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackStatus {
+    Closed,
+    Opened,
+    Changing,
+    Stopped,
+    Playing,
+    Paused,
+}
+
+/// Repeat mode of a session.
+// Mirrors MediaPlaybackAutoRepeatMode
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoRepeatMode {
+    None,
+    Track,
+    List,
+}
+
+/// Which controls a session has enabled.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Default)]
+pub struct PlaybackControls {
+    pub is_play_enabled: bool,
+    pub is_pause_enabled: bool,
+    pub is_next_enabled: bool,
+    pub is_previous_enabled: bool,
+    pub is_playback_position_enabled: bool,
+}
+
+/// Playback state of a session; `None` fields are unknown.
+#[allow(missing_docs)]
+#[derive(Debug, Clone)]
+pub struct PlaybackInfo {
+    pub playback_status: Option<PlaybackStatus>,
+    pub auto_repeat_mode: Option<AutoRepeatMode>,
+    pub is_shuffle_active: Option<bool>,
+    pub controls: Option<PlaybackControls>,
+}
+
+/// Timeline of the current track.
+#[derive(Debug, Clone)]
+pub struct TimelineProperties {
+    /// Where the track starts, usually zero.
+    pub start_time: Duration,
+    /// Where the track ends; zero if the session has no timeline.
+    pub end_time: Duration,
+    /// The position as of `last_updated_time`.
+    pub position: Duration,
+    /// When the session last reported `position`.
+    pub last_updated_time: DateTime<Utc>,
+}
+
+/// Textual metadata of the current track.
+#[allow(missing_docs)]
+#[derive(Debug, Clone)]
+pub struct MediaProperties {
+    pub title: String,
+    pub artist: String,
+    pub album_title: Option<String>,
+    pub album_artist: Option<String>,
+}
+
+/// Identifies a registered [`EventHandler`], so that it can be removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventRegistrationToken(pub i64);
+
+/// Called by a backend when one of its events fires.
+pub type EventHandler = Box<dyn Fn() + Send + Sync + 'static>;
+
+// Bookkeeping for backends that have to dispatch their own events rather than
+// leaning on the platform to do it
+#[allow(dead_code)]
+#[derive(Default)]
+pub(crate) struct HandlerList {
+    next_token: i64,
+    handlers: BTreeMap<i64, Arc<dyn Fn() + Send + Sync + 'static>>,
+}
+
+#[allow(dead_code)]
+impl HandlerList {
+    pub fn add(&mut self, handler: EventHandler) -> EventRegistrationToken {
+        self.next_token += 1;
+        self.handlers.insert(self.next_token, Arc::from(handler));
+        EventRegistrationToken(self.next_token)
+    }
+
+    pub fn remove(&mut self, token: EventRegistrationToken) {
+        self.handlers.remove(&token.0);
+    }
+
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    // the handlers run with the list unlocked, as they may well add or
+    // remove handlers themselves, or wait on someone who does
+    pub fn fire(list: &Mutex<HandlerList>) {
+        let handlers: Vec<_> = list.lock().unwrap().handlers.values().cloned().collect();
+        for handler in handlers {
+            handler();
+        }
+    }
+}
+
+/// A single media session, as exposed by the platform.
+///
+/// Getters return a snapshot of the current state; `None` means the backend
+/// could not provide that piece of information right now. The `try_*`
+/// commands resolve to whether the session accepted them. Two values are
+/// equal when they stand for the same session, which is how sessions sharing
+/// an AUMID are told apart.
+#[allow(missing_docs)]
+pub trait MediaSession: Clone + PartialEq + Send + Sync + 'static {
+    fn source_app_user_model_id(&self) -> Option<String>;
+
+    fn playback_info(&self) -> Option<PlaybackInfo>;
+    fn timeline_properties(&self) -> Option<TimelineProperties>;
+    fn media_properties(&self) -> Option<MediaProperties>;
+    fn thumbnail(&self) -> Option<ArtData>;
+
+    fn try_play(&self) -> impl Future<Output = bool> + Send;
+    fn try_pause(&self) -> impl Future<Output = bool> + Send;
+    fn try_toggle_play_pause(&self) -> impl Future<Output = bool> + Send;
+    fn try_stop(&self) -> impl Future<Output = bool> + Send;
+    fn try_skip_next(&self) -> impl Future<Output = bool> + Send;
+    fn try_skip_previous(&self) -> impl Future<Output = bool> + Send;
+    fn try_change_shuffle_active(&self, value: bool) -> impl Future<Output = bool> + Send;
+    fn try_change_auto_repeat_mode(
+        &self,
+        value: AutoRepeatMode,
+    ) -> impl Future<Output = bool> + Send;
+    // position is expressed in 100-nanosecond ticks, like the SMTC does
+    fn try_change_playback_position(&self, position: i64) -> impl Future<Output = bool> + Send;
+
+    fn playback_info_changed(&self, handler: EventHandler) -> EventRegistrationToken;
+    fn media_properties_changed(&self, handler: EventHandler) -> EventRegistrationToken;
+    fn timeline_properties_changed(&self, handler: EventHandler) -> EventRegistrationToken;
+
+    fn remove_playback_info_changed(&self, token: EventRegistrationToken);
+    fn remove_media_properties_changed(&self, token: EventRegistrationToken);
+    fn remove_timeline_properties_changed(&self, token: EventRegistrationToken);
+}
+
+/// The entry point of a backend: it enumerates sessions and tells us when
+/// they come and go.
+#[allow(missing_docs)]
+pub trait MediaSessionManager: Clone + Send + Sync + 'static {
+    type Session: MediaSession;
+
+    fn request() -> impl Future<Output = Option<Self>> + Send;
+
+    fn get_sessions(&self) -> Option<Vec<Self::Session>>;
+    fn get_current_session(&self) -> Option<Self::Session>;
+
+    fn get_friendly_name(&self, aumid: &str) -> impl Future<Output = Option<String>> + Send;
+
+    fn sessions_changed(&self, handler: EventHandler) -> EventRegistrationToken;
+    fn current_session_changed(&self, handler: EventHandler) -> EventRegistrationToken;
+
+    fn remove_sessions_changed(&self, token: EventRegistrationToken);
+    fn remove_current_session_changed(&self, token: EventRegistrationToken);
+}
@@ -0,0 +1,1209 @@
+//! MPRIS players on D-Bus.
+
+use std::{
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use tokio::task::JoinHandle;
+use zbus::{
+    fdo::{DBusProxy, NameOwnerChangedStream, PropertiesProxy},
+    names::InterfaceName,
+    zvariant::{ObjectPath, Optional, Value},
+    CacheProperties, Connection,
+};
+
+use crate::{
+    backend::{
+        AutoRepeatMode, EventHandler, EventRegistrationToken, HandlerList, MediaProperties,
+        MediaSession, MediaSessionManager, PlaybackControls, PlaybackInfo, PlaybackStatus,
+        TimelineProperties,
+    },
+    types::ArtData,
+};
+
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+// How far a re-read position may drift from the one we extrapolated before
+// the timeline counts as changed
+const POSITION_TOLERANCE: Duration = Duration::from_millis(500);
+
+// The proxy macro makes its generated types public, and they have no business
+// in our docs
+#[allow(missing_docs)]
+mod proxy {
+    use zbus::zvariant::ObjectPath;
+
+    #[zbus::proxy(
+        interface = "org.mpris.MediaPlayer2",
+        default_path = "/org/mpris/MediaPlayer2"
+    )]
+    trait MediaPlayer2 {
+        #[zbus(property)]
+        fn identity(&self) -> zbus::Result<String>;
+    }
+
+    #[zbus::proxy(
+        interface = "org.mpris.MediaPlayer2.Player",
+        default_path = "/org/mpris/MediaPlayer2"
+    )]
+    trait MediaPlayer2Player {
+        fn play(&self) -> zbus::Result<()>;
+        fn pause(&self) -> zbus::Result<()>;
+        fn play_pause(&self) -> zbus::Result<()>;
+        fn stop(&self) -> zbus::Result<()>;
+        fn next(&self) -> zbus::Result<()>;
+        fn previous(&self) -> zbus::Result<()>;
+        fn seek(&self, offset: i64) -> zbus::Result<()>;
+        fn set_position(&self, track_id: &ObjectPath<'_>, position: i64) -> zbus::Result<()>;
+
+        #[zbus(signal)]
+        fn seeked(&self, position: i64) -> zbus::Result<()>;
+
+        #[zbus(property)]
+        fn position(&self) -> zbus::Result<i64>;
+
+        #[zbus(property)]
+        fn set_shuffle(&self, value: bool) -> zbus::Result<()>;
+
+        #[zbus(property)]
+        fn set_loop_status(&self, value: &str) -> zbus::Result<()>;
+    }
+}
+
+use proxy::{MediaPlayer2PlayerProxy, MediaPlayer2Proxy};
+
+// Everything we know about a player, kept up to date by its signals so that
+// the getters never have to go through the bus
+#[derive(Default)]
+struct SessionState {
+    playback_status: Option<PlaybackStatus>,
+    loop_status: Option<AutoRepeatMode>,
+    shuffle: Option<bool>,
+
+    can_control: bool,
+    can_play: bool,
+    can_pause: bool,
+    can_go_next: bool,
+    can_go_previous: bool,
+    can_seek: bool,
+
+    title: String,
+    artists: Vec<String>,
+    album: Option<String>,
+    album_artists: Option<Vec<String>>,
+    length: Duration,
+    track_id: Option<String>,
+    art_url: Option<String>,
+
+    position: Duration,
+    position_updated: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct StateChanges {
+    playback_info: bool,
+    media_properties: bool,
+    timeline: bool,
+}
+
+// Peels the variant boxes that a{sv} dictionaries wrap every value into
+fn unwrap_variant<'a>(value: &'a Value<'a>) -> &'a Value<'a> {
+    match value {
+        Value::Value(inner) => unwrap_variant(inner),
+        _ => value,
+    }
+}
+
+fn value_to_string(value: &Value<'_>) -> Option<String> {
+    match unwrap_variant(value) {
+        Value::Str(x) => Some(x.to_string()),
+        Value::ObjectPath(x) => Some(x.to_string()),
+        _ => None,
+    }
+}
+
+fn value_to_strings(value: &Value<'_>) -> Option<Vec<String>> {
+    match unwrap_variant(value) {
+        Value::Array(x) => Some(x.iter().filter_map(value_to_string).collect()),
+        // some players send a plain string where a list is expected
+        Value::Str(x) => Some(vec![x.to_string()]),
+        _ => None,
+    }
+}
+
+fn value_to_i64(value: &Value<'_>) -> Option<i64> {
+    match unwrap_variant(value) {
+        Value::I64(x) => Some(*x),
+        Value::U64(x) => i64::try_from(*x).ok(),
+        Value::I32(x) => Some(*x as i64),
+        Value::U32(x) => Some(*x as i64),
+        _ => None,
+    }
+}
+
+fn value_to_bool(value: &Value<'_>) -> Option<bool> {
+    match unwrap_variant(value) {
+        Value::Bool(x) => Some(*x),
+        _ => None,
+    }
+}
+
+fn microseconds_to_duration(value: i64) -> Duration {
+    Duration::from_micros(value.max(0) as u64)
+}
+
+fn playback_status_from_str(status: &str) -> Option<PlaybackStatus> {
+    match status {
+        "Playing" => Some(PlaybackStatus::Playing),
+        "Paused" => Some(PlaybackStatus::Paused),
+        "Stopped" => Some(PlaybackStatus::Stopped),
+        _ => None,
+    }
+}
+
+fn loop_status_from_str(status: &str) -> Option<AutoRepeatMode> {
+    match status {
+        "None" => Some(AutoRepeatMode::None),
+        "Track" => Some(AutoRepeatMode::Track),
+        "Playlist" => Some(AutoRepeatMode::List),
+        _ => None,
+    }
+}
+
+fn loop_status_to_str(mode: AutoRepeatMode) -> &'static str {
+    match mode {
+        AutoRepeatMode::None => "None",
+        AutoRepeatMode::Track => "Track",
+        AutoRepeatMode::List => "Playlist",
+    }
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// Only local art can be read without pulling in an HTTP client, which covers
+// most native players; browsers usually hand out a file:// cache path too
+fn get_cover_art_data(art_url: &str) -> Option<ArtData> {
+    let path = percent_decode(art_url.strip_prefix("file://")?);
+    let data = std::fs::read(&path).ok()?;
+
+    let mimetype = match path.rsplit('.').next().map(|x| x.to_lowercase()).as_deref() {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("bmp") => "image/bmp",
+        _ => "application/octet-stream",
+    };
+
+    Some(ArtData {
+        data,
+        mimetype: mimetype.to_string(),
+    })
+}
+
+impl SessionState {
+    fn apply_metadata(&mut self, metadata: &Value<'_>) {
+        self.title = String::new();
+        self.artists = Vec::new();
+        self.album = None;
+        self.album_artists = None;
+        self.length = Duration::ZERO;
+        self.track_id = None;
+        self.art_url = None;
+
+        if let Value::Dict(dict) = unwrap_variant(metadata) {
+            for (key, value) in dict.iter() {
+                let Some(key) = value_to_string(key) else {
+                    continue;
+                };
+                match key.as_str() {
+                    "xesam:title" => self.title = value_to_string(value).unwrap_or_default(),
+                    "xesam:artist" => self.artists = value_to_strings(value).unwrap_or_default(),
+                    "xesam:album" => self.album = value_to_string(value),
+                    "xesam:albumArtist" => self.album_artists = value_to_strings(value),
+                    "mpris:length" => {
+                        self.length = microseconds_to_duration(value_to_i64(value).unwrap_or(0))
+                    }
+                    "mpris:trackid" => self.track_id = value_to_string(value),
+                    "mpris:artUrl" => self.art_url = value_to_string(value),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn apply(&mut self, name: &str, value: &Value<'_>, changes: &mut StateChanges) {
+        match name {
+            "PlaybackStatus" => {
+                // re-anchor first, or the old status extrapolates the position
+                self.set_position_to(self.current_position());
+                self.playback_status =
+                    value_to_string(value).and_then(|x| playback_status_from_str(&x));
+                changes.playback_info = true;
+            }
+            "LoopStatus" => {
+                self.loop_status = value_to_string(value).and_then(|x| loop_status_from_str(&x));
+                changes.playback_info = true;
+            }
+            "Shuffle" => {
+                self.shuffle = value_to_bool(value);
+                changes.playback_info = true;
+            }
+            "CanControl" => {
+                self.can_control = value_to_bool(value).unwrap_or(false);
+                changes.playback_info = true;
+            }
+            "CanPlay" => {
+                self.can_play = value_to_bool(value).unwrap_or(false);
+                changes.playback_info = true;
+            }
+            "CanPause" => {
+                self.can_pause = value_to_bool(value).unwrap_or(false);
+                changes.playback_info = true;
+            }
+            "CanGoNext" => {
+                self.can_go_next = value_to_bool(value).unwrap_or(false);
+                changes.playback_info = true;
+            }
+            "CanGoPrevious" => {
+                self.can_go_previous = value_to_bool(value).unwrap_or(false);
+                changes.playback_info = true;
+            }
+            "CanSeek" => {
+                self.can_seek = value_to_bool(value).unwrap_or(false);
+                changes.playback_info = true;
+            }
+            "Metadata" => {
+                let length = self.length;
+                self.apply_metadata(value);
+                changes.media_properties = true;
+                changes.timeline |= self.length != length;
+            }
+            "Position" => {
+                self.set_position(value_to_i64(value).unwrap_or(0));
+                changes.timeline = true;
+            }
+            "Rate" => changes.timeline = true,
+            _ => {}
+        }
+    }
+
+    fn set_position(&mut self, position: i64) {
+        self.set_position_to(microseconds_to_duration(position));
+    }
+
+    fn set_position_to(&mut self, position: Duration) {
+        self.position = position;
+        self.position_updated = Utc::now();
+    }
+
+    fn current_position(&self) -> Duration {
+        if self.playback_status == Some(PlaybackStatus::Playing) {
+            let elapsed = Utc::now() - self.position_updated;
+            return self.position + elapsed.to_std().unwrap_or_default();
+        }
+        self.position
+    }
+}
+
+struct SessionInner {
+    bus_name: String,
+    aumid: String,
+
+    properties: PropertiesProxy<'static>,
+    player: MediaPlayer2PlayerProxy<'static>,
+
+    state: Mutex<SessionState>,
+
+    playback_info_changed: Mutex<HandlerList>,
+    media_properties_changed: Mutex<HandlerList>,
+    timeline_properties_changed: Mutex<HandlerList>,
+
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl SessionInner {
+    // Returns whether the position moved other than by just playing on
+    async fn refresh_position(&self) -> bool {
+        let Ok(position) = self.player.position().await else {
+            return false;
+        };
+        let mut state = self.state.lock().unwrap();
+        let expected = state.current_position();
+        state.set_position(position);
+        state.position.abs_diff(expected) > POSITION_TOLERANCE
+    }
+
+    async fn refresh_all(&self) -> StateChanges {
+        let mut changes = StateChanges::default();
+        let interface = InterfaceName::from_static_str_unchecked(PLAYER_INTERFACE);
+        if let Ok(properties) = self
+            .properties
+            .get_all(Optional::from(Some(interface)))
+            .await
+        {
+            let mut state = self.state.lock().unwrap();
+            for (name, value) in properties.iter() {
+                state.apply(name, value, &mut changes);
+            }
+        }
+        changes
+    }
+
+    async fn handle_changes(&self, mut changes: StateChanges) {
+        // Position is never part of PropertiesChanged, so whenever anything
+        // that could move it happens we go and ask for it
+        if changes.playback_info || changes.media_properties {
+            changes.timeline |= self.refresh_position().await;
+        }
+
+        if changes.playback_info {
+            HandlerList::fire(&self.playback_info_changed);
+        }
+        if changes.media_properties {
+            HandlerList::fire(&self.media_properties_changed);
+        }
+        if changes.timeline {
+            HandlerList::fire(&self.timeline_properties_changed);
+        }
+    }
+}
+
+impl Drop for SessionInner {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.lock().unwrap().take() {
+            task.abort();
+        }
+    }
+}
+
+async fn listen_session(
+    inner: Weak<SessionInner>,
+    properties: PropertiesProxy<'static>,
+    player: MediaPlayer2PlayerProxy<'static>,
+) {
+    let Ok(mut properties_changed) = properties.receive_properties_changed().await else {
+        return;
+    };
+    let Ok(mut seeked) = player.receive_seeked().await else {
+        return;
+    };
+
+    loop {
+        tokio::select! {
+            Some(signal) = properties_changed.next() => {
+                let Some(inner) = inner.upgrade() else {
+                    break;
+                };
+                let Ok(args) = signal.args() else {
+                    continue;
+                };
+                if args.interface_name().as_str() != PLAYER_INTERFACE {
+                    continue;
+                }
+
+                let mut changes = StateChanges::default();
+                {
+                    let mut state = inner.state.lock().unwrap();
+                    for (name, value) in args.changed_properties().iter() {
+                        state.apply(name, value, &mut changes);
+                    }
+                }
+                if !args.invalidated_properties().is_empty() {
+                    let refreshed = inner.refresh_all().await;
+                    changes.playback_info |= refreshed.playback_info;
+                    changes.media_properties |= refreshed.media_properties;
+                    changes.timeline |= refreshed.timeline;
+                }
+
+                inner.handle_changes(changes).await;
+            }
+            Some(signal) = seeked.next() => {
+                let Some(inner) = inner.upgrade() else {
+                    break;
+                };
+                if let Ok(args) = signal.args() {
+                    inner.state.lock().unwrap().set_position(*args.position());
+                    HandlerList::fire(&inner.timeline_properties_changed);
+                }
+            }
+            else => break,
+        }
+    }
+}
+
+/// A single MPRIS player, identified by its `org.mpris.MediaPlayer2.*` bus name.
+///
+/// The AUMID of an MPRIS session is the part of the bus name after the
+/// `org.mpris.MediaPlayer2.` prefix, e.g. `spotify` or `firefox.instance_1_42`.
+#[derive(Clone)]
+pub struct MprisSession {
+    inner: Arc<SessionInner>,
+}
+
+impl MprisSession {
+    async fn new(conn: &Connection, bus_name: String) -> Option<Self> {
+        let aumid = bus_name.strip_prefix(MPRIS_PREFIX)?.to_string();
+
+        let properties = PropertiesProxy::builder(conn)
+            .destination(bus_name.clone())
+            .ok()?
+            .path(MPRIS_PATH)
+            .ok()?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+            .ok()?;
+
+        let player = MediaPlayer2PlayerProxy::builder(conn)
+            .destination(bus_name.clone())
+            .ok()?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+            .ok()?;
+
+        let inner = Arc::new(SessionInner {
+            bus_name,
+            aumid,
+
+            properties: properties.clone(),
+            player: player.clone(),
+
+            state: Mutex::new(SessionState::default()),
+
+            playback_info_changed: Mutex::new(HandlerList::default()),
+            media_properties_changed: Mutex::new(HandlerList::default()),
+            timeline_properties_changed: Mutex::new(HandlerList::default()),
+
+            task: Mutex::new(None),
+        });
+
+        let task = tokio::spawn(listen_session(Arc::downgrade(&inner), properties, player));
+        *inner.task.lock().unwrap() = Some(task);
+
+        inner.refresh_all().await;
+        inner.refresh_position().await;
+
+        Some(MprisSession { inner })
+    }
+
+    fn bus_name(&self) -> &str {
+        &self.inner.bus_name
+    }
+}
+
+impl PartialEq for MprisSession {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl MediaSession for MprisSession {
+    fn source_app_user_model_id(&self) -> Option<String> {
+        Some(self.inner.aumid.clone())
+    }
+
+    fn playback_info(&self) -> Option<PlaybackInfo> {
+        let state = self.inner.state.lock().unwrap();
+
+        Some(PlaybackInfo {
+            playback_status: state.playback_status,
+            auto_repeat_mode: state.loop_status,
+            is_shuffle_active: state.shuffle,
+            controls: Some(PlaybackControls {
+                is_play_enabled: state.can_control && state.can_play,
+                is_pause_enabled: state.can_control && state.can_pause,
+                is_next_enabled: state.can_control && state.can_go_next,
+                is_previous_enabled: state.can_control && state.can_go_previous,
+                is_playback_position_enabled: state.can_control && state.can_seek,
+            }),
+        })
+    }
+
+    fn timeline_properties(&self) -> Option<TimelineProperties> {
+        let state = self.inner.state.lock().unwrap();
+
+        Some(TimelineProperties {
+            start_time: Duration::ZERO,
+            end_time: state.length,
+            position: state.position,
+            last_updated_time: state.position_updated,
+        })
+    }
+
+    fn media_properties(&self) -> Option<MediaProperties> {
+        let state = self.inner.state.lock().unwrap();
+
+        Some(MediaProperties {
+            title: state.title.clone(),
+            artist: state.artists.join(", "),
+            album_title: state.album.clone(),
+            album_artist: state.album_artists.as_ref().map(|x| x.join(", ")),
+        })
+    }
+
+    fn thumbnail(&self) -> Option<ArtData> {
+        let art_url = self.inner.state.lock().unwrap().art_url.clone()?;
+        get_cover_art_data(&art_url)
+    }
+
+    async fn try_play(&self) -> bool {
+        self.inner.player.play().await.is_ok()
+    }
+
+    async fn try_pause(&self) -> bool {
+        self.inner.player.pause().await.is_ok()
+    }
+
+    async fn try_toggle_play_pause(&self) -> bool {
+        self.inner.player.play_pause().await.is_ok()
+    }
+
+    async fn try_stop(&self) -> bool {
+        self.inner.player.stop().await.is_ok()
+    }
+
+    async fn try_skip_next(&self) -> bool {
+        self.inner.player.next().await.is_ok()
+    }
+
+    async fn try_skip_previous(&self) -> bool {
+        self.inner.player.previous().await.is_ok()
+    }
+
+    async fn try_change_shuffle_active(&self, value: bool) -> bool {
+        self.inner.player.set_shuffle(value).await.is_ok()
+    }
+
+    async fn try_change_auto_repeat_mode(&self, value: AutoRepeatMode) -> bool {
+        self.inner
+            .player
+            .set_loop_status(loop_status_to_str(value))
+            .await
+            .is_ok()
+    }
+
+    async fn try_change_playback_position(&self, position: i64) -> bool {
+        // ticks of 100ns to microseconds
+        let position = position / 10;
+
+        let (track_id, current) = {
+            let state = self.inner.state.lock().unwrap();
+            (state.track_id.clone(), state.current_position())
+        };
+
+        if let Some(track_id) = track_id.and_then(|x| ObjectPath::try_from(x).ok()) {
+            return self
+                .inner
+                .player
+                .set_position(&track_id, position)
+                .await
+                .is_ok();
+        }
+
+        // without a track id SetPosition is ignored, so we seek relatively
+        let offset = position - current.as_micros() as i64;
+        self.inner.player.seek(offset).await.is_ok()
+    }
+
+    fn playback_info_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.inner
+            .playback_info_changed
+            .lock()
+            .unwrap()
+            .add(handler)
+    }
+
+    fn media_properties_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.inner
+            .media_properties_changed
+            .lock()
+            .unwrap()
+            .add(handler)
+    }
+
+    fn timeline_properties_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.inner
+            .timeline_properties_changed
+            .lock()
+            .unwrap()
+            .add(handler)
+    }
+
+    fn remove_playback_info_changed(&self, token: EventRegistrationToken) {
+        self.inner
+            .playback_info_changed
+            .lock()
+            .unwrap()
+            .remove(token);
+    }
+
+    fn remove_media_properties_changed(&self, token: EventRegistrationToken) {
+        self.inner
+            .media_properties_changed
+            .lock()
+            .unwrap()
+            .remove(token);
+    }
+
+    fn remove_timeline_properties_changed(&self, token: EventRegistrationToken) {
+        self.inner
+            .timeline_properties_changed
+            .lock()
+            .unwrap()
+            .remove(token);
+    }
+}
+
+struct ManagerInner {
+    conn: Connection,
+    sessions: Mutex<Vec<MprisSession>>,
+    // MPRIS has no notion of a system session, so we call current whichever
+    // player most recently started playing
+    current_session: Mutex<Option<String>>,
+
+    sessions_changed: Mutex<HandlerList>,
+    current_session_changed: Mutex<HandlerList>,
+
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl ManagerInner {
+    fn set_current_session(&self, bus_name: Option<String>) {
+        let changed = {
+            let mut current_session = self.current_session.lock().unwrap();
+            let changed = *current_session != bus_name;
+            *current_session = bus_name;
+            changed
+        };
+
+        if changed {
+            HandlerList::fire(&self.current_session_changed);
+        }
+    }
+
+    fn fallback_current_session(&self) -> Option<String> {
+        let sessions = self.sessions.lock().unwrap();
+        sessions
+            .iter()
+            .find(|x| {
+                x.inner.state.lock().unwrap().playback_status == Some(PlaybackStatus::Playing)
+            })
+            .or(sessions.first())
+            .map(|x| x.bus_name().to_string())
+    }
+
+    fn add_session(self: &Arc<Self>, session: MprisSession) {
+        session.playback_info_changed(Box::new({
+            let manager = Arc::downgrade(self);
+            let session = Arc::downgrade(&session.inner);
+            move || {
+                let (Some(manager), Some(session)) = (manager.upgrade(), session.upgrade()) else {
+                    return;
+                };
+                let is_playing =
+                    session.state.lock().unwrap().playback_status == Some(PlaybackStatus::Playing);
+                if is_playing {
+                    manager.set_current_session(Some(session.bus_name.clone()));
+                }
+            }
+        }));
+
+        self.sessions.lock().unwrap().push(session);
+    }
+
+    fn remove_session(&self, bus_name: &str) -> bool {
+        let removed = {
+            let mut sessions = self.sessions.lock().unwrap();
+            let len = sessions.len();
+            sessions.retain(|x| x.bus_name() != bus_name);
+            sessions.len() != len
+        };
+
+        let is_current = self.current_session.lock().unwrap().as_deref() == Some(bus_name);
+        if removed && is_current {
+            self.set_current_session(self.fallback_current_session());
+        }
+
+        removed
+    }
+}
+
+impl Drop for ManagerInner {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.lock().unwrap().take() {
+            task.abort();
+        }
+    }
+}
+
+async fn listen_manager(
+    inner: Weak<ManagerInner>,
+    conn: Connection,
+    mut name_owner_changed: NameOwnerChangedStream<'static>,
+) {
+    while let Some(signal) = name_owner_changed.next().await {
+        let Some(inner) = inner.upgrade() else {
+            break;
+        };
+        let Ok(args) = signal.args() else {
+            continue;
+        };
+
+        let bus_name = args.name().to_string();
+        if !bus_name.starts_with(MPRIS_PREFIX) {
+            continue;
+        }
+
+        // a new owner for the same name is a new player as far as we care
+        let mut changed = inner.remove_session(&bus_name);
+
+        if args.new_owner().is_some() {
+            if let Some(session) = MprisSession::new(&conn, bus_name).await {
+                inner.add_session(session);
+                changed = true;
+            }
+        }
+
+        if changed {
+            if inner.current_session.lock().unwrap().is_none() {
+                inner.set_current_session(inner.fallback_current_session());
+            }
+            HandlerList::fire(&inner.sessions_changed);
+        }
+    }
+}
+
+/// Enumerates MPRIS players on a D-Bus connection, usually the session bus.
+#[derive(Clone)]
+pub struct MprisSessionManager {
+    inner: Arc<ManagerInner>,
+}
+
+impl MprisSessionManager {
+    /// Tracks the players on `conn` rather than on the session bus.
+    pub async fn with_connection(conn: Connection) -> Option<Self> {
+        let dbus = DBusProxy::new(&conn).await.ok()?;
+
+        // subscribe before listing, or we could miss a player showing up in between
+        let name_owner_changed = dbus.receive_name_owner_changed().await.ok()?;
+
+        let inner = Arc::new(ManagerInner {
+            conn: conn.clone(),
+            sessions: Mutex::new(Vec::new()),
+            current_session: Mutex::new(None),
+
+            sessions_changed: Mutex::new(HandlerList::default()),
+            current_session_changed: Mutex::new(HandlerList::default()),
+
+            task: Mutex::new(None),
+        });
+
+        for name in dbus.list_names().await.ok()? {
+            if name.starts_with(MPRIS_PREFIX) {
+                if let Some(session) = MprisSession::new(&conn, name.to_string()).await {
+                    inner.add_session(session);
+                }
+            }
+        }
+        *inner.current_session.lock().unwrap() = inner.fallback_current_session();
+
+        let task = tokio::spawn(listen_manager(
+            Arc::downgrade(&inner),
+            conn,
+            name_owner_changed,
+        ));
+        *inner.task.lock().unwrap() = Some(task);
+
+        Some(MprisSessionManager { inner })
+    }
+}
+
+impl MediaSessionManager for MprisSessionManager {
+    type Session = MprisSession;
+
+    async fn request() -> Option<Self> {
+        Self::with_connection(Connection::session().await.ok()?).await
+    }
+
+    fn get_sessions(&self) -> Option<Vec<Self::Session>> {
+        Some(self.inner.sessions.lock().unwrap().clone())
+    }
+
+    fn get_current_session(&self) -> Option<Self::Session> {
+        let current_session = self.inner.current_session.lock().unwrap().clone()?;
+        self.inner
+            .sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|x| x.bus_name() == current_session)
+            .cloned()
+    }
+
+    async fn get_friendly_name(&self, aumid: &str) -> Option<String> {
+        MediaPlayer2Proxy::builder(&self.inner.conn)
+            .destination(format!("{}{}", MPRIS_PREFIX, aumid))
+            .ok()?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+            .ok()?
+            .identity()
+            .await
+            .ok()
+    }
+
+    fn sessions_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.inner.sessions_changed.lock().unwrap().add(handler)
+    }
+
+    fn current_session_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.inner
+            .current_session_changed
+            .lock()
+            .unwrap()
+            .add(handler)
+    }
+
+    fn remove_sessions_changed(&self, token: EventRegistrationToken) {
+        self.inner.sessions_changed.lock().unwrap().remove(token);
+    }
+
+    fn remove_current_session_changed(&self, token: EventRegistrationToken) {
+        self.inner
+            .current_session_changed
+            .lock()
+            .unwrap()
+            .remove(token);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        io::{BufRead, BufReader},
+        process::{Child, Command, Stdio},
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use tokio::time::timeout;
+    use zbus::{
+        connection, interface,
+        zvariant::{ObjectPath, OwnedValue, Str, Value},
+        Connection, SignalContext,
+    };
+
+    use super::MprisSessionManager;
+    use crate::{
+        backend::MediaSessionManager,
+        player::PlayerEvent,
+        playermanager::{ManagerEvent, PlayerManager},
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    // A throwaway bus, so that we neither need nor disturb a desktop session
+    struct DBusDaemon {
+        child: Child,
+        address: String,
+    }
+
+    impl DBusDaemon {
+        fn spawn() -> Option<Self> {
+            let mut child = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+
+            let mut address = String::new();
+            BufReader::new(child.stdout.take()?)
+                .read_line(&mut address)
+                .ok()?;
+
+            Some(DBusDaemon {
+                child,
+                address: address.trim().to_string(),
+            })
+        }
+
+        async fn connect(&self) -> Connection {
+            connection::Builder::address(self.address.as_str())
+                .unwrap()
+                .build()
+                .await
+                .unwrap()
+        }
+    }
+
+    impl Drop for DBusDaemon {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+
+    struct FakeRoot;
+
+    #[interface(name = "org.mpris.MediaPlayer2")]
+    impl FakeRoot {
+        #[zbus(property)]
+        fn identity(&self) -> String {
+            String::from("Fake Player")
+        }
+    }
+
+    struct FakePlayer {
+        playback_status: String,
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[interface(name = "org.mpris.MediaPlayer2.Player")]
+    impl FakePlayer {
+        fn play(&self) {
+            self.calls.lock().unwrap().push(String::from("Play"));
+        }
+
+        fn set_position(&self, _track_id: ObjectPath<'_>, position: i64) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("SetPosition {}", position));
+        }
+
+        #[zbus(signal)]
+        async fn seeked(ctxt: &SignalContext<'_>, position: i64) -> zbus::Result<()>;
+
+        #[zbus(property)]
+        fn playback_status(&self) -> String {
+            self.playback_status.clone()
+        }
+
+        #[zbus(property)]
+        fn loop_status(&self) -> String {
+            String::from("Playlist")
+        }
+
+        #[zbus(property)]
+        fn shuffle(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn metadata(&self) -> HashMap<String, OwnedValue> {
+            let mut metadata = HashMap::new();
+            metadata.insert(
+                String::from("mpris:trackid"),
+                OwnedValue::from(ObjectPath::from_static_str_unchecked("/track/1")),
+            );
+            metadata.insert(
+                String::from("mpris:length"),
+                OwnedValue::from(180_000_000i64),
+            );
+            metadata.insert(
+                String::from("xesam:title"),
+                OwnedValue::from(Str::from("Song")),
+            );
+            metadata.insert(
+                String::from("xesam:artist"),
+                OwnedValue::try_from(Value::from(vec!["Artist A", "Artist B"])).unwrap(),
+            );
+            metadata.insert(
+                String::from("xesam:album"),
+                OwnedValue::from(Str::from("Album")),
+            );
+            metadata
+        }
+
+        #[zbus(property)]
+        fn position(&self) -> i64 {
+            42_000_000
+        }
+
+        #[zbus(property)]
+        fn can_control(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_play(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_pause(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_go_next(&self) -> bool {
+            false
+        }
+
+        #[zbus(property)]
+        fn can_go_previous(&self) -> bool {
+            false
+        }
+
+        #[zbus(property)]
+        fn can_seek(&self) -> bool {
+            true
+        }
+    }
+
+    async fn next_manager_event(
+        player_manager: &mut PlayerManager<MprisSessionManager>,
+    ) -> ManagerEvent {
+        timeout(TIMEOUT, player_manager.poll_next_event())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn fake_player_round_trip() {
+        let Some(daemon) = DBusDaemon::spawn() else {
+            eprintln!("dbus-daemon not available, skipping");
+            return;
+        };
+
+        let session_manager = MprisSessionManager::with_connection(daemon.connect().await)
+            .await
+            .unwrap();
+        let mut player_manager = PlayerManager::from_session_manager(session_manager.clone());
+        assert!(matches!(
+            next_manager_event(&mut player_manager).await,
+            ManagerEvent::SessionsChanged
+        ));
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let fake = connection::Builder::address(daemon.address.as_str())
+            .unwrap()
+            .name("org.mpris.MediaPlayer2.fake")
+            .unwrap()
+            .serve_at(
+                "/org/mpris/MediaPlayer2",
+                FakePlayer {
+                    playback_status: String::from("Playing"),
+                    calls: calls.clone(),
+                },
+            )
+            .unwrap()
+            .serve_at("/org/mpris/MediaPlayer2", FakeRoot)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        loop {
+            if let ManagerEvent::SessionsChanged = next_manager_event(&mut player_manager).await {
+                break;
+            }
+        }
+        player_manager.update_sessions(None);
+        assert_eq!(player_manager.get_sessions_keys(), vec!["fake"]);
+        // looked up on the bus the manager was given, not the desktop's
+        assert_eq!(
+            session_manager.get_friendly_name("fake").await.as_deref(),
+            Some("Fake Player")
+        );
+
+        let player = player_manager.get_session(&String::from("fake")).unwrap();
+        let status = player.lock().await.get_status().await;
+        assert_eq!(status.status, "Playing");
+        assert_eq!(status.is_loop, "List");
+        assert!(status.shuffle);
+        assert!(status.capabilities.can_seek);
+        assert!(!status.capabilities.can_go_next);
+        let metadata = status.metadata.unwrap();
+        assert_eq!(metadata.title, "Song");
+        assert_eq!(metadata.artist, "Artist A, Artist B");
+        assert_eq!(metadata.album.as_deref(), Some("Album"));
+        assert_eq!(metadata.length, 180f64);
+        assert_eq!(status.elapsed.unwrap().how_much, 42f64);
+
+        let iface = fake
+            .object_server()
+            .interface::<_, FakePlayer>("/org/mpris/MediaPlayer2")
+            .await
+            .unwrap();
+        iface.get_mut().await.playback_status = String::from("Paused");
+        iface
+            .get()
+            .await
+            .playback_status_changed(iface.signal_context())
+            .await
+            .unwrap();
+
+        loop {
+            let event = timeout(TIMEOUT, player.lock().await.poll_next_event())
+                .await
+                .unwrap()
+                .unwrap();
+            if let PlayerEvent::PlaybackInfoChanged { status, .. } = event {
+                assert_eq!(status, "Paused");
+                break;
+            }
+        }
+        assert_eq!(player.lock().await.get_playback_status(), "Paused");
+
+        // pausing left the position where it was, so the first timeline
+        // event is the one of the seek
+        FakePlayer::seeked(iface.signal_context(), 90_000_000)
+            .await
+            .unwrap();
+        loop {
+            let event = timeout(TIMEOUT, player.lock().await.poll_next_event())
+                .await
+                .unwrap()
+                .unwrap();
+            if let PlayerEvent::TimelinePropertiesChanged { position } = event {
+                assert_eq!(position.unwrap().how_much, 90f64);
+                break;
+            }
+        }
+
+        assert!(player.lock().await.play().await);
+        assert!(player.lock().await.set_position(10f64).await);
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![String::from("Play"), String::from("SetPosition 10000000")]
+        );
+
+        drop(iface);
+        drop(fake);
+        loop {
+            if let ManagerEvent::SessionsChanged = next_manager_event(&mut player_manager).await {
+                break;
+            }
+        }
+        player_manager.update_sessions(None);
+        assert!(player_manager.get_sessions_keys().is_empty());
+    }
+}
@@ -0,0 +1,419 @@
+//! The Windows System Media Transport Controls.
+//!
+//! The traits are implemented straight on the WinRT session manager and
+//! session types, which are re-exported here. Their equality is COM
+//! identity, so a session compares equal to itself across `GetSessions`
+//! calls.
+
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+
+use windows::{
+    core::HSTRING,
+    ApplicationModel,
+    Foundation::{self, TypedEventHandler},
+    Media::{
+        Control::GlobalSystemMediaTransportControlsSessionPlaybackStatus,
+        MediaPlaybackAutoRepeatMode,
+    },
+    Storage::Streams::{self, DataReader, IRandomAccessStreamReference},
+    System,
+};
+
+pub use windows::Media::Control::{
+    GlobalSystemMediaTransportControlsSession, GlobalSystemMediaTransportControlsSessionManager,
+};
+
+use crate::{
+    backend::{
+        AutoRepeatMode, EventHandler, EventRegistrationToken, MediaProperties, MediaSession,
+        MediaSessionManager, PlaybackControls, PlaybackInfo, PlaybackStatus, TimelineProperties,
+    },
+    types::ArtData,
+};
+
+// I don't want to deal with libraries
+fn shitty_windows_epoch_to_utc(shitty_time: i64) -> Option<DateTime<Utc>> {
+    // 64-bit value representing the number of 100-nanosecond intervals since January 1, 1601 (UTC)
+    const TICKS_PER_MILLISECOND: i64 = 10000;
+    const UNIX_TIMESTAMP_DIFFERENCE: i64 = 0x019DB1DED53E8000;
+    let unix_ts = (shitty_time - UNIX_TIMESTAMP_DIFFERENCE) / TICKS_PER_MILLISECOND;
+    Utc.timestamp_millis_opt(unix_ts).single()
+}
+
+fn to_token(token: Foundation::EventRegistrationToken) -> EventRegistrationToken {
+    EventRegistrationToken(token.Value)
+}
+
+fn from_token(token: EventRegistrationToken) -> Foundation::EventRegistrationToken {
+    Foundation::EventRegistrationToken { Value: token.0 }
+}
+
+fn to_playback_status(
+    status: GlobalSystemMediaTransportControlsSessionPlaybackStatus,
+) -> Option<PlaybackStatus> {
+    match status {
+        GlobalSystemMediaTransportControlsSessionPlaybackStatus::Closed => {
+            Some(PlaybackStatus::Closed)
+        }
+        GlobalSystemMediaTransportControlsSessionPlaybackStatus::Opened => {
+            Some(PlaybackStatus::Opened)
+        }
+        GlobalSystemMediaTransportControlsSessionPlaybackStatus::Changing => {
+            Some(PlaybackStatus::Changing)
+        }
+        GlobalSystemMediaTransportControlsSessionPlaybackStatus::Stopped => {
+            Some(PlaybackStatus::Stopped)
+        }
+        GlobalSystemMediaTransportControlsSessionPlaybackStatus::Playing => {
+            Some(PlaybackStatus::Playing)
+        }
+        GlobalSystemMediaTransportControlsSessionPlaybackStatus::Paused => {
+            Some(PlaybackStatus::Paused)
+        }
+        _ => None,
+    }
+}
+
+fn to_autorepeat(mode: MediaPlaybackAutoRepeatMode) -> Option<AutoRepeatMode> {
+    match mode {
+        MediaPlaybackAutoRepeatMode::None => Some(AutoRepeatMode::None),
+        MediaPlaybackAutoRepeatMode::Track => Some(AutoRepeatMode::Track),
+        MediaPlaybackAutoRepeatMode::List => Some(AutoRepeatMode::List),
+        _ => None,
+    }
+}
+
+fn from_autorepeat(mode: AutoRepeatMode) -> MediaPlaybackAutoRepeatMode {
+    match mode {
+        AutoRepeatMode::None => MediaPlaybackAutoRepeatMode::None,
+        AutoRepeatMode::Track => MediaPlaybackAutoRepeatMode::Track,
+        AutoRepeatMode::List => MediaPlaybackAutoRepeatMode::List,
+    }
+}
+
+async fn get_session_player_name_for_user(aumid: &str) -> Option<String> {
+    let user = {
+        let user = System::User::FindAllAsync()
+            .ok()?
+            .await
+            .ok()?
+            .GetAt(0)
+            .ok()?;
+        Some(user)
+    }?;
+
+    let player_name =
+        ApplicationModel::AppInfo::GetFromAppUserModelIdForUser(&user, &HSTRING::from(aumid))
+            .ok()?
+            .DisplayInfo()
+            .ok()?
+            .DisplayName()
+            .ok()?
+            .to_string();
+
+    if player_name.eq(aumid) {
+        return Some(player_name.trim_end_matches(".exe").to_string());
+    }
+
+    Some(player_name)
+}
+
+async fn get_session_player_name_global(aumid: &str) -> Option<String> {
+    let player_name = ApplicationModel::AppInfo::GetFromAppUserModelId(&HSTRING::from(aumid))
+        .ok()?
+        .DisplayInfo()
+        .ok()?
+        .DisplayName()
+        .ok()?;
+
+    if player_name.to_string().eq(aumid) && player_name.to_string().ends_with(".exe") {
+        return Some(
+            player_name
+                .to_string()
+                .strip_suffix(".exe")
+                .unwrap_or_default()
+                .to_string(),
+        );
+    }
+
+    Some(player_name.to_string())
+}
+
+fn get_cover_art_data(thumbnail: IRandomAccessStreamReference) -> Option<ArtData> {
+    if let Ok(_async) = thumbnail.OpenReadAsync() {
+        if let Ok(stream) = _async.get() {
+            let size = stream.Size().unwrap_or(0);
+            let content_type = stream.ContentType().unwrap_or_default();
+
+            if stream.CanRead().unwrap_or(false) && size > 0 {
+                let result_buffer = 'rt: {
+                    if let Ok(buffer) = Streams::Buffer::Create(size as u32) {
+                        if let Ok(_async) =
+                            stream.ReadAsync(&buffer, size as u32, Default::default())
+                        {
+                            if let Ok(result_buffer) = _async.get() {
+                                break 'rt Some(result_buffer);
+                            }
+                        }
+                    }
+                    None
+                };
+
+                if let Some(result_buffer) = result_buffer {
+                    let size = result_buffer.Length().unwrap_or(0);
+
+                    if let Ok(data_reader) = DataReader::FromBuffer(&result_buffer) {
+                        let mut data = vec![0; size as usize];
+                        data_reader.ReadBytes(data.as_mut()).unwrap_or_default();
+
+                        if let Ok(_async) = stream.FlushAsync() {
+                            let _ = _async.get();
+                        }
+
+                        let _ = stream.Close();
+
+                        return Some(ArtData {
+                            data,
+                            mimetype: content_type.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+impl MediaSession for GlobalSystemMediaTransportControlsSession {
+    fn source_app_user_model_id(&self) -> Option<String> {
+        Some(self.SourceAppUserModelId().ok()?.to_string())
+    }
+
+    fn playback_info(&self) -> Option<PlaybackInfo> {
+        let playback_info = self.GetPlaybackInfo().ok()?;
+
+        Some(PlaybackInfo {
+            playback_status: playback_info
+                .PlaybackStatus()
+                .ok()
+                .and_then(to_playback_status),
+            auto_repeat_mode: playback_info
+                .AutoRepeatMode()
+                .ok()
+                .and_then(|x| x.Value().ok())
+                .and_then(to_autorepeat),
+            is_shuffle_active: playback_info
+                .IsShuffleActive()
+                .ok()
+                .map(|x| x.Value().unwrap_or(false)),
+            controls: playback_info
+                .Controls()
+                .ok()
+                .map(|controls| PlaybackControls {
+                    is_play_enabled: controls.IsPlayEnabled().unwrap_or(false),
+                    is_pause_enabled: controls.IsPauseEnabled().unwrap_or(false),
+                    is_next_enabled: controls.IsNextEnabled().unwrap_or(false),
+                    is_previous_enabled: controls.IsPreviousEnabled().unwrap_or(false),
+                    is_playback_position_enabled: controls
+                        .IsPlaybackPositionEnabled()
+                        .unwrap_or(false),
+                }),
+        })
+    }
+
+    fn timeline_properties(&self) -> Option<TimelineProperties> {
+        let timeline_properties = self.GetTimelineProperties().ok()?;
+        // a time that does not convert makes the whole timeline unusable
+        let last_updated_time = match timeline_properties.LastUpdatedTime() {
+            Ok(x) => shitty_windows_epoch_to_utc(x.UniversalTime)?,
+            Err(_) => Default::default(),
+        };
+
+        Some(TimelineProperties {
+            start_time: timeline_properties
+                .StartTime()
+                .map(Duration::from)
+                .unwrap_or_default(),
+            end_time: timeline_properties
+                .EndTime()
+                .map(Duration::from)
+                .unwrap_or_default(),
+            position: timeline_properties
+                .Position()
+                .map(Duration::from)
+                .unwrap_or_default(),
+            last_updated_time,
+        })
+    }
+
+    fn media_properties(&self) -> Option<MediaProperties> {
+        let info = self.TryGetMediaPropertiesAsync().ok()?.get().ok()?;
+
+        Some(MediaProperties {
+            title: info.Title().unwrap_or_default().to_string(),
+            artist: info.Artist().unwrap_or_default().to_string(),
+            album_title: info.AlbumTitle().ok().map(|x| x.to_string()),
+            album_artist: info.AlbumArtist().ok().map(|x| x.to_string()),
+        })
+    }
+
+    fn thumbnail(&self) -> Option<ArtData> {
+        let info = self.TryGetMediaPropertiesAsync().ok()?.get().ok()?;
+        get_cover_art_data(info.Thumbnail().ok()?)
+    }
+
+    async fn try_play(&self) -> bool {
+        if let Ok(result) = self.TryPlayAsync() {
+            return result.await.unwrap_or(false);
+        }
+        false
+    }
+
+    async fn try_pause(&self) -> bool {
+        if let Ok(result) = self.TryPauseAsync() {
+            return result.await.unwrap_or(false);
+        }
+        false
+    }
+
+    async fn try_toggle_play_pause(&self) -> bool {
+        if let Ok(result) = self.TryTogglePlayPauseAsync() {
+            return result.await.unwrap_or(false);
+        }
+        false
+    }
+
+    async fn try_stop(&self) -> bool {
+        if let Ok(result) = self.TryStopAsync() {
+            return result.await.unwrap_or(false);
+        }
+        false
+    }
+
+    async fn try_skip_next(&self) -> bool {
+        if let Ok(result) = self.TrySkipNextAsync() {
+            return result.await.unwrap_or(false);
+        }
+        false
+    }
+
+    async fn try_skip_previous(&self) -> bool {
+        if let Ok(result) = self.TrySkipPreviousAsync() {
+            return result.await.unwrap_or(false);
+        }
+        false
+    }
+
+    async fn try_change_shuffle_active(&self, value: bool) -> bool {
+        if let Ok(result) = self.TryChangeShuffleActiveAsync(value) {
+            return result.await.unwrap_or(false);
+        }
+        false
+    }
+
+    async fn try_change_auto_repeat_mode(&self, value: AutoRepeatMode) -> bool {
+        if let Ok(result) = self.TryChangeAutoRepeatModeAsync(from_autorepeat(value)) {
+            return result.await.unwrap_or(false);
+        }
+        false
+    }
+
+    async fn try_change_playback_position(&self, position: i64) -> bool {
+        if let Ok(result) = self.TryChangePlaybackPositionAsync(position) {
+            return result.await.unwrap_or(false);
+        }
+        false
+    }
+
+    fn playback_info_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.PlaybackInfoChanged(&TypedEventHandler::new(move |_, _| {
+            handler();
+            Ok(())
+        }))
+        .map(to_token)
+        .unwrap_or_default()
+    }
+
+    fn media_properties_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.MediaPropertiesChanged(&TypedEventHandler::new(move |_, _| {
+            handler();
+            Ok(())
+        }))
+        .map(to_token)
+        .unwrap_or_default()
+    }
+
+    fn timeline_properties_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.TimelinePropertiesChanged(&TypedEventHandler::new(move |_, _| {
+            handler();
+            Ok(())
+        }))
+        .map(to_token)
+        .unwrap_or_default()
+    }
+
+    fn remove_playback_info_changed(&self, token: EventRegistrationToken) {
+        let _ = self.RemovePlaybackInfoChanged(from_token(token));
+    }
+
+    fn remove_media_properties_changed(&self, token: EventRegistrationToken) {
+        let _ = self.RemoveMediaPropertiesChanged(from_token(token));
+    }
+
+    fn remove_timeline_properties_changed(&self, token: EventRegistrationToken) {
+        let _ = self.RemoveTimelinePropertiesChanged(from_token(token));
+    }
+}
+
+impl MediaSessionManager for GlobalSystemMediaTransportControlsSessionManager {
+    type Session = GlobalSystemMediaTransportControlsSession;
+
+    async fn request() -> Option<Self> {
+        GlobalSystemMediaTransportControlsSessionManager::RequestAsync()
+            .ok()?
+            .await
+            .ok()
+    }
+
+    fn get_sessions(&self) -> Option<Vec<Self::Session>> {
+        Some(self.GetSessions().ok()?.into_iter().collect())
+    }
+
+    fn get_current_session(&self) -> Option<Self::Session> {
+        self.GetCurrentSession().ok()
+    }
+
+    async fn get_friendly_name(&self, aumid: &str) -> Option<String> {
+        get_session_player_name_for_user(aumid)
+            .await
+            .or(get_session_player_name_global(aumid).await)
+    }
+
+    fn sessions_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.SessionsChanged(&TypedEventHandler::new(move |_, _| {
+            handler();
+            Ok(())
+        }))
+        .map(to_token)
+        .unwrap_or_default()
+    }
+
+    fn current_session_changed(&self, handler: EventHandler) -> EventRegistrationToken {
+        self.CurrentSessionChanged(&TypedEventHandler::new(move |_, _| {
+            handler();
+            Ok(())
+        }))
+        .map(to_token)
+        .unwrap_or_default()
+    }
+
+    fn remove_sessions_changed(&self, token: EventRegistrationToken) {
+        let _ = self.RemoveSessionsChanged(from_token(token));
+    }
+
+    fn remove_current_session_changed(&self, token: EventRegistrationToken) {
+        let _ = self.RemoveCurrentSessionChanged(from_token(token));
+    }
+}
@@ -0,0 +1,389 @@
+//! Fan-out of events to any number of receivers.
+//!
+//! Every [`EventReceiver`] has a queue of its own, so receivers read at their
+//! own pace and never take events away from each other. A queue holds at most
+//! [`QueueOptions::capacity`] events; what happens when an event arrives at a
+//! full queue is up to its [`OverflowPolicy`], and every event a receiver
+//! loses that way is counted in [`EventReceiver::dropped`].
+
+use std::{
+    collections::VecDeque,
+    mem::discriminant,
+    sync::{Arc, Condvar, Mutex, Weak},
+};
+
+use tokio::{
+    runtime::{Handle, RuntimeFlavor},
+    sync::Notify,
+    task::block_in_place,
+};
+
+/// What to do with an event that arrives at a full queue.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Make room by dropping the oldest queued event.
+    #[default]
+    DropOldest,
+    /// Make room by dropping the oldest queued event of the same kind, which
+    /// the new one supersedes; the oldest event overall if there is none.
+    Coalesce,
+    /// Make the sender wait until the receiver makes room. Senders include
+    /// the backend delivering its events and tasks of the tokio runtime, such
+    /// as the debouncing and position timers, which all stall meanwhile: only
+    /// use this for receivers that are sure to keep up.
+    ///
+    /// A task waits with [`tokio::task::block_in_place`], so that the other
+    /// tasks of its worker, the receiver's among them, keep running. On a
+    /// `current_thread` runtime nothing could run while it waits, so sends
+    /// from there drop the oldest event instead, and so do the queues behind
+    /// `poll_next_event`, which nobody may be polling.
+    Block,
+}
+
+/// How big a receiver's queue is and what happens when it is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueOptions {
+    /// How many events can be waiting at most; at least one.
+    pub capacity: usize,
+    /// What to do when that many are waiting.
+    pub policy: OverflowPolicy,
+}
+
+impl Default for QueueOptions {
+    fn default() -> Self {
+        QueueOptions {
+            capacity: 256,
+            policy: OverflowPolicy::default(),
+        }
+    }
+}
+
+impl QueueOptions {
+    // for the queues behind poll_next_event: nothing says anyone polls them,
+    // and a full one that blocks would stall the backend for good
+    pub(crate) fn without_blocking(self) -> Self {
+        QueueOptions {
+            policy: match self.policy {
+                OverflowPolicy::Block => OverflowPolicy::DropOldest,
+                policy => policy,
+            },
+            ..self
+        }
+    }
+}
+
+struct QueueState<T> {
+    events: VecDeque<T>,
+    dropped: u64,
+    sender_gone: bool,
+    receiver_gone: bool,
+}
+
+struct Queue<T> {
+    options: QueueOptions,
+    state: Mutex<QueueState<T>>,
+    // receivers wait on `ready` for events, blocked senders on `space`
+    ready: Notify,
+    space: Condvar,
+}
+
+impl<T> Queue<T> {
+    fn push(&self, event: T) {
+        let capacity = self.options.capacity.max(1);
+        let mut state = self.state.lock().unwrap();
+
+        let runtime = Handle::try_current().ok();
+        let policy = match self.options.policy {
+            // waiting would keep the receiver from ever making room
+            OverflowPolicy::Block
+                if runtime
+                    .as_ref()
+                    .is_some_and(|x| x.runtime_flavor() == RuntimeFlavor::CurrentThread) =>
+            {
+                OverflowPolicy::DropOldest
+            }
+            policy => policy,
+        };
+
+        if state.events.len() >= capacity {
+            match policy {
+                OverflowPolicy::DropOldest => {
+                    state.events.pop_front();
+                    state.dropped += 1;
+                }
+                OverflowPolicy::Coalesce => {
+                    let kind = discriminant(&event);
+                    let index = state
+                        .events
+                        .iter()
+                        .position(|x| discriminant(x) == kind)
+                        .unwrap_or(0);
+                    state.events.remove(index);
+                    state.dropped += 1;
+                }
+                OverflowPolicy::Block => {
+                    let wait = || {
+                        self.space
+                            .wait_while(state, |x| x.events.len() >= capacity && !x.receiver_gone)
+                            .unwrap()
+                    };
+                    state = match runtime {
+                        Some(_) => block_in_place(wait),
+                        None => wait(),
+                    };
+                }
+            }
+        }
+
+        if state.receiver_gone {
+            return;
+        }
+        state.events.push_back(event);
+        drop(state);
+        self.ready.notify_one();
+    }
+
+    fn pop(&self) -> Result<T, bool> {
+        let mut state = self.state.lock().unwrap();
+        match state.events.pop_front() {
+            Some(event) => {
+                drop(state);
+                self.space.notify_one();
+                Ok(event)
+            }
+            None => Err(state.sender_gone),
+        }
+    }
+}
+
+/// Hands a copy of every event to each of its receivers.
+pub(crate) struct EventBus<T: Clone> {
+    queues: Mutex<Vec<Weak<Queue<T>>>>,
+}
+
+impl<T: Clone> EventBus<T> {
+    pub fn new() -> Self {
+        EventBus {
+            queues: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// A receiver for the events sent from now on.
+    pub fn subscribe(&self, options: QueueOptions) -> EventReceiver<T> {
+        let queue = Arc::new(Queue {
+            options,
+            state: Mutex::new(QueueState {
+                events: VecDeque::new(),
+                dropped: 0,
+                sender_gone: false,
+                receiver_gone: false,
+            }),
+            ready: Notify::new(),
+            space: Condvar::new(),
+        });
+        self.queues.lock().unwrap().push(Arc::downgrade(&queue));
+        EventReceiver { queue }
+    }
+
+    pub fn send(&self, event: T) {
+        // not holding the list while pushing, a blocked push would keep
+        // anyone else from subscribing
+        let queues: Vec<_> = {
+            let mut queues = self.queues.lock().unwrap();
+            queues.retain(|x| x.strong_count() > 0);
+            queues.iter().filter_map(Weak::upgrade).collect()
+        };
+        for queue in queues {
+            queue.push(event.clone());
+        }
+    }
+}
+
+impl<T: Clone> Drop for EventBus<T> {
+    fn drop(&mut self) {
+        for queue in self.queues.lock().unwrap().iter().filter_map(Weak::upgrade) {
+            queue.state.lock().unwrap().sender_gone = true;
+            queue.ready.notify_one();
+        }
+    }
+}
+
+/// A queue of the events of a player or manager, see the [module docs](self).
+///
+/// The receiver ends, once it has handed out what was left in its queue, when
+/// whatever it receives from is dropped.
+pub struct EventReceiver<T> {
+    queue: Arc<Queue<T>>,
+}
+
+impl<T> EventReceiver<T> {
+    /// Waits for the next event; `None` once there will be no more.
+    pub async fn recv(&self) -> Option<T> {
+        loop {
+            // registered before looking, so that a push in between still wakes us
+            let ready = self.queue.ready.notified();
+            match self.queue.pop() {
+                Ok(event) => return Some(event),
+                Err(true) => return None,
+                Err(false) => ready.await,
+            }
+        }
+    }
+
+    /// The next event if there is one already, without waiting.
+    pub fn try_recv(&self) -> Option<T> {
+        self.queue.pop().ok()
+    }
+
+    /// How many events this receiver lost to its [`OverflowPolicy`].
+    pub fn dropped(&self) -> u64 {
+        self.queue.state.lock().unwrap().dropped
+    }
+}
+
+impl<T> Drop for EventReceiver<T> {
+    fn drop(&mut self) {
+        self.queue.state.lock().unwrap().receiver_gone = true;
+        self.queue.space.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread, time::Duration};
+
+    use tokio::time::timeout;
+
+    use super::{EventBus, OverflowPolicy, QueueOptions};
+
+    fn options(capacity: usize, policy: OverflowPolicy) -> QueueOptions {
+        QueueOptions { capacity, policy }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Event {
+        A(u32),
+        B(u32),
+    }
+
+    #[test]
+    fn every_receiver_gets_every_later_event() {
+        let bus = EventBus::new();
+        bus.send(0);
+
+        let a = bus.subscribe(QueueOptions::default());
+        bus.send(1);
+        let b = bus.subscribe(QueueOptions::default());
+        bus.send(2);
+
+        assert_eq!(a.try_recv(), Some(1));
+        assert_eq!(a.try_recv(), Some(2));
+        assert_eq!(a.try_recv(), None);
+        assert_eq!(b.try_recv(), Some(2));
+        assert_eq!(b.try_recv(), None);
+
+        drop(a);
+        bus.send(3);
+        assert_eq!(bus.queues.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn receivers_drain_their_queue_before_ending() {
+        let bus = EventBus::new();
+        let receiver = bus.subscribe(QueueOptions::default());
+        bus.send(1);
+        drop(bus);
+
+        assert_eq!(receiver.recv().await, Some(1));
+        assert_eq!(receiver.recv().await, None);
+    }
+
+    #[test]
+    fn drop_oldest_keeps_the_latest_events() {
+        let bus = EventBus::new();
+        let receiver = bus.subscribe(options(2, OverflowPolicy::DropOldest));
+        let fast = bus.subscribe(options(2, OverflowPolicy::DropOldest));
+
+        for i in 0..5 {
+            bus.send(i);
+            assert_eq!(fast.try_recv(), Some(i));
+        }
+
+        assert_eq!(receiver.try_recv(), Some(3));
+        assert_eq!(receiver.try_recv(), Some(4));
+        assert_eq!(receiver.dropped(), 3);
+        assert_eq!(fast.dropped(), 0);
+    }
+
+    #[test]
+    fn coalesce_replaces_events_of_the_same_kind() {
+        let bus = EventBus::new();
+        let receiver = bus.subscribe(options(2, OverflowPolicy::Coalesce));
+
+        bus.send(Event::A(1));
+        bus.send(Event::B(1));
+        bus.send(Event::A(2));
+        bus.send(Event::A(3));
+
+        assert_eq!(receiver.try_recv(), Some(Event::B(1)));
+        assert_eq!(receiver.try_recv(), Some(Event::A(3)));
+        assert_eq!(receiver.dropped(), 2);
+    }
+
+    #[test]
+    fn block_waits_for_room() {
+        let bus = EventBus::new();
+        let receiver = bus.subscribe(options(1, OverflowPolicy::Block));
+
+        bus.send(1);
+        let sender = thread::spawn(move || {
+            bus.send(2);
+            bus
+        });
+        thread::sleep(Duration::from_millis(50));
+        assert!(!sender.is_finished());
+
+        assert_eq!(receiver.try_recv(), Some(1));
+        let bus = sender.join().unwrap();
+        assert_eq!(receiver.try_recv(), Some(2));
+        assert_eq!(receiver.dropped(), 0);
+
+        // and gives up once there is nobody to make room
+        bus.send(3);
+        let sender = thread::spawn(move || bus.send(4));
+        thread::sleep(Duration::from_millis(50));
+        drop(receiver);
+        sender.join().unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn block_lets_the_runtime_run_while_waiting() {
+        let bus = Arc::new(EventBus::new());
+        let receiver = bus.subscribe(options(1, OverflowPolicy::Block));
+        bus.send(1);
+
+        let sender = tokio::spawn({
+            let bus = bus.clone();
+            async move { bus.send(2) }
+        });
+        // the only worker is the one the sender waits on
+        let received = tokio::spawn(async move {
+            let events = (receiver.recv().await, receiver.recv().await);
+            (events, receiver.dropped())
+        });
+        let received = timeout(Duration::from_secs(5), received).await.unwrap();
+        assert_eq!(received.unwrap(), ((Some(1), Some(2)), 0));
+        sender.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn block_drops_on_a_current_thread_runtime() {
+        let bus = EventBus::new();
+        let receiver = bus.subscribe(options(1, OverflowPolicy::Block));
+
+        bus.send(1);
+        bus.send(2);
+        assert_eq!(receiver.recv().await, Some(2));
+        assert_eq!(receiver.dropped(), 1);
+    }
+}
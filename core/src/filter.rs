@@ -0,0 +1,189 @@
+//! Which sessions a [`PlayerManager`](crate::PlayerManager) keeps track of.
+//!
+//! A [`SessionFilter`] is a list of rules to allow and a list of rules to
+//! deny, each matching either the AUMID of a session or the friendly name of
+//! its app (e.g. `Spotify`). A session is tracked if no allow rule is given or
+//! any of them matches, and no deny rule matches.
+
+use regex::Regex;
+
+#[derive(Debug, Clone)]
+enum PatternKind {
+    Exact(String),
+    Regex(Regex),
+}
+
+/// Something to match a string against.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    kind: PatternKind,
+}
+
+impl Pattern {
+    /// Matches only `value` itself.
+    pub fn exact(value: &str) -> Self {
+        Pattern {
+            kind: PatternKind::Exact(value.to_string()),
+        }
+    }
+
+    /// Matches the whole string against a glob, where `*` stands for any run
+    /// of characters and `?` for any single one; case is ignored, e.g.
+    /// `*chrome*` matches `Chrome` and `com.google.Chrome`.
+    pub fn glob(glob: &str) -> Self {
+        let mut regex = String::from("(?i)^");
+        for c in glob.chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        regex.push('$');
+
+        Pattern {
+            // everything but the wildcards is escaped, so this always compiles
+            kind: PatternKind::Regex(Regex::new(&regex).unwrap()),
+        }
+    }
+
+    /// Matches wherever `regex` does; anchor it with `^` and `$` to match the
+    /// whole string.
+    pub fn regex(regex: &str) -> Result<Self, regex::Error> {
+        Ok(Pattern {
+            kind: PatternKind::Regex(Regex::new(regex)?),
+        })
+    }
+
+    /// Reads a pattern from its string form: `/.../` is a [`Pattern::regex`],
+    /// anything with a `*` or a `?` a [`Pattern::glob`], anything else
+    /// [`Pattern::exact`].
+    pub fn parse(pattern: &str) -> Result<Self, regex::Error> {
+        if let Some(regex) = pattern.strip_prefix('/').and_then(|x| x.strip_suffix('/')) {
+            return Self::regex(regex);
+        }
+        if pattern.contains(['*', '?']) {
+            return Ok(Self::glob(pattern));
+        }
+        Ok(Self::exact(pattern))
+    }
+
+    /// Whether `value` matches.
+    pub fn matches(&self, value: &str) -> bool {
+        match &self.kind {
+            PatternKind::Exact(x) => x == value,
+            PatternKind::Regex(x) => x.is_match(value),
+        }
+    }
+}
+
+/// A rule of a [`SessionFilter`].
+#[derive(Debug, Clone)]
+pub enum SessionMatcher {
+    /// Matches the App User Model ID of the session.
+    Aumid(Pattern),
+    /// Matches the friendly name of the app, as given by
+    /// [`MediaSessionManager::get_friendly_name`](crate::backend::MediaSessionManager::get_friendly_name).
+    /// Sessions whose name cannot be found never match.
+    FriendlyName(Pattern),
+}
+
+impl SessionMatcher {
+    fn matches(&self, aumid: &str, friendly_name: Option<&str>) -> bool {
+        match self {
+            SessionMatcher::Aumid(x) => x.matches(aumid),
+            SessionMatcher::FriendlyName(x) => friendly_name.is_some_and(|name| x.matches(name)),
+        }
+    }
+}
+
+/// Which sessions to track; the default tracks them all.
+#[derive(Debug, Clone, Default)]
+pub struct SessionFilter {
+    /// If not empty, only sessions matching one of these are tracked.
+    pub allow: Vec<SessionMatcher>,
+    /// Sessions matching any of these are not tracked.
+    pub deny: Vec<SessionMatcher>,
+}
+
+impl SessionFilter {
+    /// A filter denying exactly the given AUMIDs.
+    pub fn denylist(aumids: &[String]) -> Self {
+        SessionFilter {
+            allow: Vec::new(),
+            deny: aumids
+                .iter()
+                .map(|x| SessionMatcher::Aumid(Pattern::exact(x)))
+                .collect(),
+        }
+    }
+
+    /// Whether any rule needs the friendly name of the app, which has to be
+    /// looked up.
+    pub fn uses_friendly_names(&self) -> bool {
+        self.allow
+            .iter()
+            .chain(&self.deny)
+            .any(|x| matches!(x, SessionMatcher::FriendlyName(_)))
+    }
+
+    /// Whether the session of `aumid`, whose app is called `friendly_name`,
+    /// is to be tracked.
+    pub fn accepts(&self, aumid: &str, friendly_name: Option<&str>) -> bool {
+        let allowed =
+            self.allow.is_empty() || self.allow.iter().any(|x| x.matches(aumid, friendly_name));
+        allowed && !self.deny.iter().any(|x| x.matches(aumid, friendly_name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pattern, SessionFilter, SessionMatcher};
+
+    #[test]
+    fn patterns_follow_their_string_form() {
+        let exact = Pattern::parse("Spotify.exe").unwrap();
+        assert!(exact.matches("Spotify.exe"));
+        assert!(!exact.matches("spotify.exe"));
+
+        let glob = Pattern::parse("Microsoft.ZuneMusic_*").unwrap();
+        assert!(glob.matches("Microsoft.ZuneMusic_8wekyb3d8bbwe!Microsoft.ZuneMusic"));
+        assert!(glob.matches("microsoft.zunemusic_"));
+        assert!(!glob.matches("Microsoft.ZuneVideo_8wekyb3d8bbwe"));
+
+        let glob = Pattern::parse("a.?").unwrap();
+        assert!(glob.matches("a.b"));
+        assert!(!glob.matches("axb"));
+
+        let regex = Pattern::parse("/^(chrome|msedge)\\.exe$/").unwrap();
+        assert!(regex.matches("msedge.exe"));
+        assert!(!regex.matches("firefox.exe"));
+
+        assert!(Pattern::parse("/(/").is_err());
+    }
+
+    #[test]
+    fn allow_rules_then_deny_rules() {
+        let filter = SessionFilter {
+            allow: vec![
+                SessionMatcher::FriendlyName(Pattern::exact("Spotify")),
+                SessionMatcher::Aumid(Pattern::glob("*chrome*")),
+                SessionMatcher::Aumid(Pattern::glob("*firefox*")),
+            ],
+            deny: vec![SessionMatcher::Aumid(Pattern::exact("firefox.instance2"))],
+        };
+        assert!(filter.uses_friendly_names());
+
+        assert!(filter.accepts("Spotify.exe", Some("Spotify")));
+        assert!(filter.accepts("Chrome", None));
+        assert!(filter.accepts("firefox.instance1", None));
+        assert!(!filter.accepts("firefox.instance2", None));
+        assert!(!filter.accepts("vlc", Some("VLC media player")));
+        assert!(!filter.accepts("Spotify.exe", None));
+
+        let filter = SessionFilter::denylist(&[String::from("vlc")]);
+        assert!(!filter.uses_friendly_names());
+        assert!(filter.accepts("Spotify.exe", None));
+        assert!(!filter.accepts("vlc", None));
+    }
+}
@@ -0,0 +1,45 @@
+//! Tracking and control of the media sessions running on this machine.
+//!
+//! A [`PlayerManager`] keeps one [`Player`] per media session reported by a
+//! backend (the Windows System Media Transport Controls with the `winrt`
+//! feature, MPRIS players on D-Bus with the `mpris` feature) and picks which
+//! one is the active one. Both report what happens through
+//! [`ManagerEvent`]s and [`PlayerEvent`]s.
+//!
+//! ```no_run
+//! # #[cfg(feature = "mpris")]
+//! # async fn example() {
+//! use winplayer_core::{backend::mpris::MprisSessionManager, PlayerManager};
+//!
+//! let mut player_manager = PlayerManager::<MprisSessionManager>::new().await.unwrap();
+//! player_manager.update_sessions(None);
+//! if let Some(player) = player_manager.get_active_session() {
+//!     println!("{:?}", player.lock().await.get_status().await);
+//! }
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+pub mod backend;
+pub mod bus;
+pub mod filter;
+pub mod metadata;
+pub mod player;
+pub mod playermanager;
+pub mod selection;
+pub mod types;
+pub mod util;
+
+pub use bus::{EventReceiver, OverflowPolicy, QueueOptions};
+pub use filter::{Pattern, SessionFilter, SessionMatcher};
+pub use metadata::{ArtistSplitting, NormalizationRules};
+pub use player::{
+    DebounceOptions, Player, PlayerEvent, PlayerEventReceiver, PlayerOptions, PlayerSubscriber,
+};
+pub use playermanager::{ManagerEvent, PlayerManager, PlayerManagerOptions};
+pub use selection::SelectionPolicy;
+pub use types::{
+    ArtData, Capabilities, FieldChange, FieldValue, Metadata, Position, RawMetadata,
+    SessionActivity, Status,
+};
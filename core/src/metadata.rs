@@ -0,0 +1,401 @@
+//! Cleaning up the metadata sessions report.
+//!
+//! [`NormalizationRules`] fix up what browsers and video sites report, e.g.
+//! `Artist - Song (Official Video) [4K]` by `ArtistVEVO`. Then, since
+//! sessions only give a single artist string, e.g. `A, B & C feat. D`,
+//! [`ArtistSplitting`] turns it into the list of artists that
+//! [`Metadata::artists`] and [`Metadata::album_artists`] are meant to be.
+
+use std::{fmt, fs, io, path::Path};
+
+use regex::Regex;
+
+use crate::{
+    filter::Pattern,
+    types::{Metadata, RawMetadata},
+};
+
+/// The separators [`ArtistSplitting::common`] splits on.
+pub const COMMON_SEPARATORS: &[&str] = &[", ", " & ", " feat. ", " ft. ", " x ", ";"];
+
+/// How to split artist strings into lists of artists; the default leaves
+/// them whole.
+#[derive(Debug, Clone, Default)]
+pub struct ArtistSplitting {
+    /// What goes between two artists, e.g. `" & "`; case is ignored.
+    pub separators: Vec<String>,
+    /// Names never split, even though they contain a separator, e.g.
+    /// `Earth, Wind & Fire`; case is ignored.
+    pub protected: Vec<String>,
+}
+
+impl ArtistSplitting {
+    /// Splits on [`COMMON_SEPARATORS`].
+    pub fn common() -> Self {
+        ArtistSplitting {
+            separators: COMMON_SEPARATORS.iter().map(|x| x.to_string()).collect(),
+            protected: Vec::new(),
+        }
+    }
+
+    /// The artists in `artist`, trimmed, in order. A string with nothing to
+    /// split, the empty one included, comes back as is.
+    pub fn split(&self, artist: &str) -> Vec<String> {
+        let mut artists = Vec::new();
+        let mut start = 0;
+        let mut i = 0;
+        while i < artist.len() {
+            if let Some(len) = longest_match(artist, i, &self.protected) {
+                i += len;
+            } else if let Some(len) = longest_match(artist, i, &self.separators) {
+                artists.push(&artist[start..i]);
+                i += len;
+                start = i;
+            } else {
+                i += artist[i..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+        artists.push(&artist[start..]);
+
+        let artists: Vec<String> = artists
+            .into_iter()
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+            .map(String::from)
+            .collect();
+        if artists.is_empty() {
+            return vec![artist.to_string()];
+        }
+        artists
+    }
+
+    /// Fills [`Metadata::artists`] and [`Metadata::album_artists`] from
+    /// [`Metadata::artist`] and [`Metadata::album_artist`].
+    pub fn apply(&self, metadata: &mut Metadata) {
+        metadata.artists = self.split(&metadata.artist);
+        metadata.album_artists = metadata.album_artist.as_deref().map(|x| self.split(x));
+    }
+}
+
+/// The rules of [`NormalizationRules::common`], which also show the syntax of
+/// a rules file.
+pub const COMMON_RULES: &str = r#"# one rule per line, lines starting with `#` are comments
+#
+# app <pattern>             the sessions the rules apply to, as an exact AUMID,
+#                           a glob or a /regex/; without any, all of them
+# title-separator "<text>"  splits `<artist><text><title>` titles in two, unless
+#                           the session reports an artist of its own
+# strip-title <regex>       removed from titles
+# strip-artist <regex>      removed from artists and album artists
+
+app *chrome*
+app *chromium*
+app *msedge*
+app *firefox*
+app *brave*
+app *opera*
+app *vivaldi*
+
+title-separator " - "
+title-separator " – "
+title-separator " — "
+
+strip-title (?i)\s*[(\[][^)\]]*\b(official|video|audio|lyrics?|visuali[sz]er)\b[^)\]]*[)\]]
+strip-title (?i)\s*[(\[](4k|hd|hq|mv)[)\]]
+strip-artist (?i)\s*vevo$
+strip-artist (?i)\s+-\s+topic$
+"#;
+
+/// What is wrong with a rules file.
+#[derive(Debug)]
+pub enum RulesError {
+    /// It could not be read.
+    Io(io::Error),
+    /// A line of it, counting from 1, does not make sense.
+    Syntax {
+        /// Where the problem is.
+        line: usize,
+        /// What the problem is.
+        message: String,
+    },
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesError::Io(error) => write!(f, "cannot read the rules: {}", error),
+            RulesError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+/// How to fix up the metadata of some sessions; the default changes nothing.
+///
+/// In order, they strip [`NormalizationRules::strip_artist`] from the artist
+/// and the album artist and [`NormalizationRules::strip_title`] from the
+/// title, then split titles like `Artist - Song` into their artist and
+/// title. Titles are only split when the artist the session reports is not
+/// a real one: empty, a channel name [`NormalizationRules::strip_artist`]
+/// changed, or the same artist the title starts with; so that `Song - Live`
+/// by `Band` stays as it is. Whatever they change, the values the session reported are kept in
+/// [`Metadata::raw`].
+#[derive(Debug, Clone, Default)]
+pub struct NormalizationRules {
+    /// The sessions to apply the rules to, matching their AUMID; if empty,
+    /// all of them.
+    pub apps: Vec<Pattern>,
+    /// What goes between the artist and the title in titles holding both,
+    /// e.g. `" - "`. The first one found splits the title.
+    pub title_separators: Vec<String>,
+    /// Removed from titles, e.g. `(Official Video)`.
+    pub strip_title: Vec<Regex>,
+    /// Removed from artists, e.g. `VEVO` at the end.
+    pub strip_artist: Vec<Regex>,
+}
+
+impl NormalizationRules {
+    /// The rules of [`COMMON_RULES`], for browsers.
+    pub fn common() -> Self {
+        // they are ours, we know they parse
+        Self::parse(COMMON_RULES).unwrap()
+    }
+
+    /// Reads the rules in `rules`, written like [`COMMON_RULES`].
+    pub fn parse(rules: &str) -> Result<Self, RulesError> {
+        let mut parsed = NormalizationRules::default();
+        for (i, line) in rules.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let syntax = |message: String| RulesError::Syntax {
+                line: i + 1,
+                message,
+            };
+
+            let (rule, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let value = value.trim();
+            if value.is_empty() {
+                return Err(syntax(format!("`{}` needs a value", rule)));
+            }
+            let regex = || Regex::new(value).map_err(|x| syntax(x.to_string()));
+            match rule {
+                "app" => parsed
+                    .apps
+                    .push(Pattern::parse(value).map_err(|x| syntax(x.to_string()))?),
+                "title-separator" => parsed.title_separators.push(
+                    value
+                        .strip_prefix('"')
+                        .and_then(|x| x.strip_suffix('"'))
+                        .unwrap_or(value)
+                        .to_string(),
+                ),
+                "strip-title" => parsed.strip_title.push(regex()?),
+                "strip-artist" => parsed.strip_artist.push(regex()?),
+                _ => return Err(syntax(format!("unknown rule `{}`", rule))),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Reads the rules in the file at `path`, see [`NormalizationRules::parse`].
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, RulesError> {
+        Self::parse(&fs::read_to_string(path).map_err(RulesError::Io)?)
+    }
+
+    /// Whether the rules are meant for the session of `aumid`.
+    pub fn applies_to(&self, aumid: &str) -> bool {
+        self.apps.is_empty() || self.apps.iter().any(|x| x.matches(aumid))
+    }
+
+    /// Fixes up `metadata` whatever session it comes from; the fields
+    /// derived from the ones it changes, such as [`Metadata::artists`], are
+    /// left alone.
+    pub fn apply(&self, metadata: &mut Metadata) {
+        let raw = RawMetadata {
+            album_artist: metadata.album_artist.clone(),
+            artist: metadata.artist.clone(),
+            title: metadata.title.clone(),
+        };
+
+        metadata.artist = strip(&metadata.artist, &self.strip_artist);
+        metadata.album_artist = metadata
+            .album_artist
+            .as_deref()
+            .map(|x| strip(x, &self.strip_artist));
+        metadata.title = strip(&metadata.title, &self.strip_title);
+
+        // a stripped artist is a channel name rather than the artist
+        let own_artist = !metadata.artist.is_empty() && metadata.artist == raw.artist;
+        let separator = self
+            .title_separators
+            .iter()
+            .filter(|x| !x.is_empty())
+            .filter_map(|x| Some((metadata.title.find(x.as_str())?, x.len())))
+            .min();
+        if let Some((at, len)) = separator {
+            let artist = metadata.title[..at].trim();
+            let title = metadata.title[at + len..].trim();
+            let split = !own_artist || artist.eq_ignore_ascii_case(&metadata.artist);
+            if split && !artist.is_empty() && !title.is_empty() {
+                (metadata.artist, metadata.title) = (artist.to_string(), title.to_string());
+            }
+        }
+
+        let changed = metadata.album_artist != raw.album_artist
+            || metadata.artist != raw.artist
+            || metadata.title != raw.title;
+        if changed {
+            metadata.raw = Some(raw);
+        }
+    }
+}
+
+fn strip(value: &str, patterns: &[Regex]) -> String {
+    let mut value = value.to_string();
+    for pattern in patterns {
+        value = pattern.replace_all(&value, "").into_owned();
+    }
+    value.trim().to_string()
+}
+
+// the length of the longest of `patterns` found at `at`
+fn longest_match(value: &str, at: usize, patterns: &[String]) -> Option<usize> {
+    patterns
+        .iter()
+        .filter(|x| !x.is_empty())
+        .filter(|x| {
+            value
+                .get(at..at + x.len())
+                .is_some_and(|y| y.eq_ignore_ascii_case(x))
+        })
+        .map(String::len)
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArtistSplitting, NormalizationRules, RulesError};
+    use crate::types::{Metadata, RawMetadata};
+
+    fn metadata(artist: &str, title: &str) -> Metadata {
+        Metadata {
+            album: None,
+            album_artist: None,
+            album_artists: None,
+            artist: artist.to_string(),
+            artists: vec![artist.to_string()],
+            art_data: None,
+            id: None,
+            length: 0.0,
+            title: title.to_string(),
+            raw: None,
+        }
+    }
+
+    #[test]
+    fn artists_are_split_around_protected_names() {
+        let splitting = ArtistSplitting::common();
+        assert_eq!(
+            splitting.split("A, B & C feat. D"),
+            vec!["A", "B", "C", "D"]
+        );
+        assert_eq!(splitting.split("A FEAT. B x C;D"), vec!["A", "B", "C", "D"]);
+        assert_eq!(splitting.split("Mötley Crüe & Ñ"), vec!["Mötley Crüe", "Ñ"]);
+        assert_eq!(splitting.split("Just One"), vec!["Just One"]);
+        assert_eq!(splitting.split(""), vec![""]);
+        assert_eq!(splitting.split(", "), vec![", "]);
+
+        let splitting = ArtistSplitting {
+            protected: vec![
+                String::from("Earth, Wind & Fire"),
+                String::from("Simon & Garfunkel"),
+            ],
+            ..ArtistSplitting::common()
+        };
+        assert_eq!(
+            splitting.split("earth, wind & fire & Simon & Garfunkel, X"),
+            vec!["earth, wind & fire", "Simon & Garfunkel", "X"]
+        );
+
+        let splitting = ArtistSplitting::default();
+        assert_eq!(splitting.split("A & B"), vec!["A & B"]);
+    }
+
+    #[test]
+    fn browser_metadata_is_normalized() {
+        let rules = NormalizationRules::common();
+        assert!(rules.applies_to("Chrome"));
+        assert!(rules.applies_to("firefox.instance_1_42"));
+        assert!(!rules.applies_to("Spotify.exe"));
+
+        let mut video = metadata("ArtistVEVO", "Artist - Song (Official Video) [4K]");
+        rules.apply(&mut video);
+        assert_eq!(
+            (video.artist.as_str(), video.title.as_str()),
+            ("Artist", "Song")
+        );
+        assert_eq!(
+            video.raw,
+            Some(RawMetadata {
+                album_artist: None,
+                artist: String::from("ArtistVEVO"),
+                title: String::from("Artist - Song (Official Video) [4K]"),
+            })
+        );
+
+        let mut topic = metadata("Artist - Topic", "Song (feat. Someone)");
+        rules.apply(&mut topic);
+        assert_eq!(topic.artist, "Artist");
+        assert_eq!(topic.title, "Song (feat. Someone)");
+
+        let mut clean = metadata("Artist", "Song");
+        rules.apply(&mut clean);
+        assert!(clean.raw.is_none());
+
+        let mut live = metadata("Band", "Song - Live");
+        rules.apply(&mut live);
+        assert_eq!(
+            (live.artist.as_str(), live.title.as_str()),
+            ("Band", "Song - Live")
+        );
+        assert!(live.raw.is_none());
+
+        let mut channel = metadata("Artist", "Artist - Song");
+        rules.apply(&mut channel);
+        assert_eq!(
+            (channel.artist.as_str(), channel.title.as_str()),
+            ("Artist", "Song")
+        );
+    }
+
+    #[test]
+    fn rules_files_are_checked() {
+        let rules = NormalizationRules::parse(
+            "# mine\napp Spotify.exe\ntitle-separator \" | \"\nstrip-title \\s*- Remastered.*$\n",
+        )
+        .unwrap();
+        assert!(rules.applies_to("Spotify.exe"));
+        let mut song = metadata("", "Artist | Song - Remastered 2011");
+        rules.apply(&mut song);
+        assert_eq!(
+            (song.artist.as_str(), song.title.as_str()),
+            ("Artist", "Song")
+        );
+
+        assert!(matches!(
+            NormalizationRules::parse("app x\nstrip-title (\n"),
+            Err(RulesError::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            NormalizationRules::parse("strip-everything x"),
+            Err(RulesError::Syntax { line: 1, .. })
+        ));
+        assert!(matches!(
+            NormalizationRules::from_file("/nonexistent/rules"),
+            Err(RulesError::Io(_))
+        ));
+    }
+}
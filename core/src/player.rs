@@ -0,0 +1,1477 @@
+//! A single media session.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, Weak,
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use tokio::{runtime::Handle, task::JoinHandle, time::Instant};
+
+use crate::{
+    backend::{
+        AutoRepeatMode, EventHandler, EventRegistrationToken, MediaSession, PlaybackInfo,
+        PlaybackStatus,
+    },
+    bus::{EventBus, EventReceiver, QueueOptions},
+    metadata::{ArtistSplitting, NormalizationRules},
+    types::{Capabilities, FieldChange, Metadata, Position, Status},
+    util::{
+        compute_length, compute_position, diff_status, get_playback_status, get_repeat_mode,
+        get_session_capabilities, get_session_metadata, get_session_status, get_shuffle,
+    },
+};
+
+/// Something changed in a session.
+///
+/// Every event carries the state of the session as of when it fired, so
+/// there is no need to go and ask for it again.
+#[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names, clippy::large_enum_variant)]
+pub enum PlayerEvent {
+    /// Playback status, repeat, shuffle or the available controls changed.
+    PlaybackInfoChanged {
+        /// Same as [`Status::status`].
+        status: String,
+        /// Same as [`Status::is_loop`].
+        is_loop: String,
+        /// Same as [`Status::shuffle`].
+        shuffle: bool,
+        /// Same as [`Status::capabilities`].
+        capabilities: Capabilities,
+    },
+    /// The metadata of the track changed.
+    MediaPropertiesChanged {
+        /// Same as [`Status::metadata`], without the cover art: reading it
+        /// is slow, ask [`Player::get_status`] for it.
+        metadata: Option<Metadata>,
+    },
+    /// The timeline changed: a new position, a seek or a new track length.
+    TimelinePropertiesChanged {
+        /// Same as [`Status::elapsed`].
+        position: Option<Position>,
+    },
+    /// A different track started, as told by [`Metadata::id`].
+    ///
+    /// Unlike [`PlayerEvent::MediaPropertiesChanged`] this does not fire for
+    /// new cover art or repeated notifications about the same track, nor for
+    /// the metadata without a title some apps report halfway through a
+    /// change. It comes right after the `MediaPropertiesChanged` it was
+    /// derived from.
+    TrackChanged {
+        /// The track before, `None` if there was none since the player was
+        /// created.
+        previous: Option<Metadata>,
+        /// The track now playing, without the cover art like in
+        /// `MediaPropertiesChanged`.
+        current: Metadata,
+    },
+    /// The position jumped: the timeline reports a position too far from the
+    /// one expected by letting the previous one run for the time in between.
+    /// It comes right after the `TimelinePropertiesChanged` that revealed it.
+    ///
+    /// How far is too far is [`PlayerOptions::seek_threshold`]. A new track,
+    /// told by its metadata or its length, is not a seek, and neither is a
+    /// track starting over once it reaches its end.
+    Seeked {
+        /// Where playback would have been without the seek, in seconds.
+        from: f64,
+        /// Where playback is now, in seconds.
+        to: f64,
+    },
+    /// The position as of now, sent every [`PlayerOptions::position_interval`]
+    /// while playing.
+    PositionTick {
+        /// Same as [`Player::get_position`] with `true`.
+        position: Position,
+    },
+    /// Some fields of [`Status`] changed, whichever of the events above
+    /// brought them. It comes right after that event and the `TrackChanged`
+    /// or `Seeked` derived from it.
+    ///
+    /// See [`diff_status`] for which fields are compared.
+    StatusChanged {
+        /// What changed, never empty.
+        changes: Vec<FieldChange>,
+    },
+}
+
+impl PlayerEvent {
+    fn playback_info_changed<S: MediaSession>(session: &S) -> Self {
+        let playback_info = session.playback_info();
+        PlayerEvent::PlaybackInfoChanged {
+            status: get_playback_status(playback_info.as_ref()),
+            is_loop: get_repeat_mode(playback_info.as_ref()),
+            shuffle: get_shuffle(playback_info.as_ref()),
+            capabilities: get_session_capabilities(session),
+        }
+    }
+
+    fn media_properties_changed<S: MediaSession>(session: &S, cleanup: &MetadataCleanup) -> Self {
+        PlayerEvent::MediaPropertiesChanged {
+            metadata: cleanup.metadata(session),
+        }
+    }
+
+    fn track_changed(track: &Mutex<Option<Metadata>>, metadata: &Metadata) -> Option<Self> {
+        let id = metadata.id.as_ref()?;
+        // apps tend to clear the metadata before filling in the next track
+        if metadata.title.is_empty() {
+            return None;
+        }
+
+        let mut track = track.lock().unwrap();
+        if track.as_ref().and_then(|x| x.id.as_ref()) == Some(id) {
+            return None;
+        }
+        Some(PlayerEvent::TrackChanged {
+            previous: track.replace(metadata.clone()),
+            current: metadata.clone(),
+        })
+    }
+
+    fn timeline_properties_changed<S: MediaSession>(session: &S) -> Self {
+        PlayerEvent::TimelinePropertiesChanged {
+            position: compute_position(
+                session.timeline_properties().as_ref(),
+                session.playback_info().as_ref(),
+                false,
+            ),
+        }
+    }
+}
+
+/// The receiving end of [`Player::poll_next_event`], usable without holding on
+/// to the player itself.
+///
+/// Clones share the same cursor: every event goes to only one of them. Use
+/// [`Player::subscribe`] for a cursor of your own.
+#[derive(Clone)]
+pub struct PlayerEventReceiver {
+    rx: Arc<EventReceiver<PlayerEvent>>,
+}
+
+impl PlayerEventReceiver {
+    /// Waits for the next event; `None` once the player is gone.
+    pub async fn recv(&self) -> Option<PlayerEvent> {
+        self.rx.recv().await
+    }
+
+    /// How many events were dropped because nobody polled them in time.
+    pub fn dropped(&self) -> u64 {
+        self.rx.dropped()
+    }
+}
+
+/// Subscribes to the events of a player like [`Player::subscribe`], usable
+/// without holding on to the player itself.
+///
+/// It does not keep the player alive: once the player is dropped, the
+/// receivers it hands out end right away.
+#[derive(Clone)]
+pub struct PlayerSubscriber {
+    bus: Weak<EventBus<PlayerEvent>>,
+    options: QueueOptions,
+}
+
+impl PlayerSubscriber {
+    /// Same as [`Player::subscribe`].
+    pub fn subscribe(&self) -> EventReceiver<PlayerEvent> {
+        self.subscribe_with(self.options)
+    }
+
+    /// Same as [`Player::subscribe_with`].
+    pub fn subscribe_with(&self, options: QueueOptions) -> EventReceiver<PlayerEvent> {
+        match self.bus.upgrade() {
+            Some(bus) => bus.subscribe(options),
+            // dropped right away, so the receiver ends
+            None => EventBus::new().subscribe(options),
+        }
+    }
+}
+
+/// How long to wait for each kind of event to settle; `None` delivers every
+/// event right away.
+///
+/// A debounced event is delivered once that long has passed without another
+/// event of the same kind, and carries the state as of then: a burst of them
+/// becomes a single event with the final state.
+#[derive(Debug, Clone, Default)]
+pub struct DebounceOptions {
+    /// Window for [`PlayerEvent::PlaybackInfoChanged`].
+    pub playback_info: Option<Duration>,
+    /// Window for [`PlayerEvent::MediaPropertiesChanged`].
+    pub media_properties: Option<Duration>,
+    /// Window for [`PlayerEvent::TimelinePropertiesChanged`].
+    pub timeline_properties: Option<Duration>,
+}
+
+/// How a [`Player`] behaves.
+#[derive(Debug, Clone)]
+pub struct PlayerOptions {
+    /// The queue behind [`Player::poll_next_event`] and the default for
+    /// [`Player::subscribe`]; the former never blocks, see
+    /// [`OverflowPolicy::Block`](crate::bus::OverflowPolicy::Block).
+    pub event_queue: QueueOptions,
+    /// Debouncing of the events, which needs the player to be created within
+    /// a tokio runtime; outside of one events are never debounced.
+    pub debounce: DebounceOptions,
+    /// How far the position has to be from the expected one to count as a
+    /// [`PlayerEvent::Seeked`]; 2 seconds by default, as sessions are not
+    /// precise about when they update their timeline.
+    pub seek_threshold: Duration,
+    /// How often to send a [`PlayerEvent::PositionTick`] while playing; `None`,
+    /// the default, sends none. Like debouncing, this needs the player to be
+    /// created within a tokio runtime.
+    pub position_interval: Option<Duration>,
+    /// How [`Metadata::artists`] and [`Metadata::album_artists`] are split
+    /// out of the artist strings; by default they are not.
+    pub artist_splitting: ArtistSplitting,
+    /// How to fix up the metadata before splitting the artists, for the
+    /// sessions the rules apply to; `None`, the default, leaves it as
+    /// reported.
+    pub normalization: Option<NormalizationRules>,
+}
+
+impl Default for PlayerOptions {
+    fn default() -> Self {
+        PlayerOptions {
+            event_queue: QueueOptions::default(),
+            debounce: DebounceOptions::default(),
+            seek_threshold: Duration::from_secs(2),
+            position_interval: None,
+            artist_splitting: ArtistSplitting::default(),
+            normalization: None,
+        }
+    }
+}
+
+// turns what a session reports into the metadata the options ask for
+pub(crate) struct MetadataCleanup {
+    normalization: Option<NormalizationRules>,
+    splitting: ArtistSplitting,
+}
+
+impl MetadataCleanup {
+    fn new(aumid: &str, options: &PlayerOptions) -> Self {
+        MetadataCleanup {
+            normalization: options
+                .normalization
+                .clone()
+                .filter(|x| x.applies_to(aumid)),
+            splitting: options.artist_splitting.clone(),
+        }
+    }
+
+    fn apply(&self, metadata: &mut Metadata) {
+        if let Some(normalization) = &self.normalization {
+            normalization.apply(metadata);
+        }
+        self.splitting.apply(metadata);
+    }
+
+    fn metadata<S: MediaSession>(&self, session: &S) -> Option<Metadata> {
+        let mut metadata = get_session_metadata(session, false)?;
+        self.apply(&mut metadata);
+        Some(metadata)
+    }
+
+    pub(crate) fn status(&self, mut status: Status) -> Status {
+        if let Some(metadata) = status.metadata.as_mut() {
+            self.apply(metadata);
+        }
+        status
+    }
+}
+
+struct Ticker {
+    interval: Duration,
+    runtime: Handle,
+    bus: Arc<EventBus<PlayerEvent>>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Ticker {
+    fn new(interval: Option<Duration>, bus: &Arc<EventBus<PlayerEvent>>) -> Option<Self> {
+        Some(Ticker {
+            interval: interval?,
+            runtime: Handle::try_current().ok()?,
+            bus: bus.clone(),
+            task: Mutex::new(None),
+        })
+    }
+
+    // restarts the ticks from now if playing, so that they do not come right
+    // after the position the session just reported, stops them otherwise
+    fn sync<S: MediaSession>(&self, session: &S) {
+        let mut task = self.task.lock().unwrap();
+        if let Some(task) = task.take() {
+            task.abort();
+        }
+        if !is_playing(session.playback_info().as_ref()) {
+            return;
+        }
+
+        let interval = self.interval;
+        let session = session.clone();
+        let bus = self.bus.clone();
+        *task = Some(self.runtime.spawn(async move {
+            let mut ticks = tokio::time::interval_at(Instant::now() + interval, interval);
+            loop {
+                ticks.tick().await;
+                let position = compute_position(
+                    session.timeline_properties().as_ref(),
+                    session.playback_info().as_ref(),
+                    true,
+                );
+                if let Some(position) = position {
+                    bus.send(PlayerEvent::PositionTick { position });
+                }
+            }
+        }));
+    }
+
+    fn stop(&self) {
+        if let Some(task) = self.task.lock().unwrap().take() {
+            task.abort();
+        }
+    }
+}
+
+// the last known position, and whether it was moving since
+struct SeekAnchor {
+    position: f64,
+    when: DateTime<Utc>,
+    playing: bool,
+    length: f64,
+}
+
+impl SeekAnchor {
+    fn position_at(&self, when: DateTime<Utc>) -> f64 {
+        if !self.playing {
+            return self.position;
+        }
+        self.position + (when - self.when).num_milliseconds() as f64 / 1000f64
+    }
+}
+
+fn is_playing(playback_info: Option<&PlaybackInfo>) -> bool {
+    playback_info.and_then(|x| x.playback_status) == Some(PlaybackStatus::Playing)
+}
+
+struct SeekTracker {
+    threshold: f64,
+    anchor: Mutex<Option<SeekAnchor>>,
+}
+
+impl SeekTracker {
+    fn new<S: MediaSession>(session: &S, threshold: Duration) -> Self {
+        let tracker = SeekTracker {
+            threshold: threshold.as_secs_f64(),
+            anchor: Mutex::new(None),
+        };
+        tracker.timeline_changed(session);
+        tracker
+    }
+
+    // play and pause change how the position moves from now on, so the
+    // anchor is moved to now under the old rules
+    fn playback_changed<S: MediaSession>(&self, session: &S) {
+        let playing = is_playing(session.playback_info().as_ref());
+        if let Some(anchor) = self.anchor.lock().unwrap().as_mut() {
+            let now = Utc::now();
+            anchor.position = anchor.position_at(now);
+            anchor.when = now;
+            anchor.playing = playing;
+        }
+    }
+
+    // what the position did on the previous track says nothing about this
+    // one, even if it happens to be as long
+    fn track_changed(&self) {
+        *self.anchor.lock().unwrap() = None;
+    }
+
+    fn timeline_changed<S: MediaSession>(&self, session: &S) -> Option<PlayerEvent> {
+        let timeline_properties = session.timeline_properties();
+        let playback_info = session.playback_info();
+        let position =
+            compute_position(timeline_properties.as_ref(), playback_info.as_ref(), false);
+
+        let mut anchor = self.anchor.lock().unwrap();
+        let (Some(position), Some(timeline_properties)) = (position, timeline_properties) else {
+            *anchor = None;
+            return None;
+        };
+        let next = SeekAnchor {
+            position: position.how_much,
+            when: position.when,
+            playing: is_playing(playback_info.as_ref()),
+            length: timeline_properties.end_time.as_secs_f64()
+                - timeline_properties.start_time.as_secs_f64(),
+        };
+
+        let seeked = anchor.as_ref().and_then(|anchor| {
+            let from = anchor.position_at(next.when);
+            let jumped = (next.position - from).abs() > self.threshold;
+            // repeating the track starts it over, without any new metadata
+            let wrapped = from >= anchor.length - self.threshold && next.position <= self.threshold;
+            let seeked = jumped && !wrapped && anchor.length == next.length;
+            seeked.then_some(PlayerEvent::Seeked {
+                from,
+                to: next.position,
+            })
+        });
+        *anchor = Some(next);
+        seeked
+    }
+}
+
+// the status as of the last event, to tell what changed since
+struct StatusSnapshot {
+    status: Mutex<Status>,
+}
+
+impl StatusSnapshot {
+    fn new<S: MediaSession>(session: &S, aumid: &str, cleanup: &MetadataCleanup) -> Self {
+        StatusSnapshot {
+            status: Mutex::new(cleanup.status(get_session_status(session, aumid, false))),
+        }
+    }
+
+    // only patches in what the event already read, reading the whole status
+    // again would mean fetching the media properties on every event
+    fn update(&self, bus: &EventBus<PlayerEvent>, update: impl FnOnce(&mut Status)) {
+        let changes = {
+            let mut last = self.status.lock().unwrap();
+            let mut status = last.clone();
+            update(&mut status);
+            let changes = diff_status(&last, &status);
+            *last = status;
+            changes
+        };
+        if !changes.is_empty() {
+            bus.send(PlayerEvent::StatusChanged { changes });
+        }
+    }
+
+    fn event_changed(&self, bus: &EventBus<PlayerEvent>, event: &PlayerEvent) {
+        self.update(bus, |status| match event {
+            PlayerEvent::PlaybackInfoChanged {
+                status: playback_status,
+                is_loop,
+                shuffle,
+                capabilities,
+            } => {
+                status.status = playback_status.clone();
+                status.is_loop = is_loop.clone();
+                status.shuffle = *shuffle;
+                status.capabilities = capabilities.clone();
+            }
+            PlayerEvent::MediaPropertiesChanged { metadata } => status.metadata = metadata.clone(),
+            _ => {}
+        });
+    }
+
+    // the length of the track comes with the timeline
+    fn timeline_changed<S: MediaSession>(&self, bus: &EventBus<PlayerEvent>, session: &S) {
+        let Some(timeline_properties) = session.timeline_properties() else {
+            return;
+        };
+        self.update(bus, |status| {
+            if let Some(metadata) = status.metadata.as_mut() {
+                metadata.length = compute_length(&timeline_properties);
+            }
+        });
+    }
+}
+
+struct DebounceState {
+    // bumped on every event, the timer only fires once it stops moving
+    generation: AtomicU64,
+    pending: AtomicBool,
+}
+
+struct Debouncer {
+    window: Duration,
+    runtime: Handle,
+    state: Arc<DebounceState>,
+}
+
+impl Debouncer {
+    fn new(window: Option<Duration>) -> Option<Self> {
+        Some(Debouncer {
+            window: window?,
+            runtime: Handle::try_current().ok()?,
+            state: Arc::new(DebounceState {
+                generation: AtomicU64::new(0),
+                pending: AtomicBool::new(false),
+            }),
+        })
+    }
+
+    fn trigger(&self, emit: Arc<dyn Fn() + Send + Sync>) {
+        self.state.generation.fetch_add(1, Ordering::SeqCst);
+        if self.state.pending.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let window = self.window;
+        let state = self.state.clone();
+        self.runtime.spawn(async move {
+            loop {
+                let seen = state.generation.load(Ordering::SeqCst);
+                tokio::time::sleep(window).await;
+                if state.generation.load(Ordering::SeqCst) == seen {
+                    break;
+                }
+            }
+            // cleared before reading the state: whatever comes in from now on
+            // starts a timer of its own, and whatever came in just before is
+            // part of what we are about to read
+            state.pending.store(false, Ordering::SeqCst);
+            emit();
+        });
+    }
+}
+
+fn event_handler<S: MediaSession>(
+    session: &S,
+    bus: &Arc<EventBus<PlayerEvent>>,
+    snapshot: &Arc<StatusSnapshot>,
+    window: Option<Duration>,
+    send: impl Fn(&S, &EventBus<PlayerEvent>, &StatusSnapshot) + Send + Sync + 'static,
+) -> EventHandler {
+    // the handler lives as long as the session does, we break the cycle by
+    // unregistering it on drop
+    let session = session.clone();
+    let bus = bus.clone();
+    let snapshot = snapshot.clone();
+    let emit = move || send(&session, &bus, &snapshot);
+
+    match Debouncer::new(window) {
+        Some(debouncer) => {
+            let emit: Arc<dyn Fn() + Send + Sync> = Arc::new(emit);
+            Box::new(move || debouncer.trigger(emit.clone()))
+        }
+        None => Box::new(emit),
+    }
+}
+
+struct EventToken {
+    playback_info_changed_token: EventRegistrationToken,
+    media_properties_changed_token: EventRegistrationToken,
+    timeline_properties_changed_token: EventRegistrationToken,
+}
+
+/// Wraps a media session, listening to its events and exposing its controls.
+pub struct Player<S: MediaSession> {
+    session: S,
+    aumid: String,
+
+    options: PlayerOptions,
+    cleanup: Arc<MetadataCleanup>,
+
+    bus: Arc<EventBus<PlayerEvent>>,
+    events: PlayerEventReceiver,
+
+    ticker: Option<Arc<Ticker>>,
+
+    event_tokens: EventToken,
+}
+
+impl<S: MediaSession> Player<S> {
+    /// Starts listening to the events of `session`, which belongs to `aumid`.
+    pub fn new(session: S, aumid: String) -> Self {
+        Self::with_options(session, aumid, PlayerOptions::default())
+    }
+
+    /// Same as [`Player::new`], with something other than the default options.
+    pub fn with_options(session: S, aumid: String, options: PlayerOptions) -> Self {
+        let bus = Arc::new(EventBus::new());
+        let events = PlayerEventReceiver {
+            rx: Arc::new(bus.subscribe(options.event_queue.without_blocking())),
+        };
+
+        let debounce = &options.debounce;
+
+        let cleanup = Arc::new(MetadataCleanup::new(&aumid, &options));
+        let snapshot = Arc::new(StatusSnapshot::new(&session, &aumid, &cleanup));
+
+        let seeks = Arc::new(SeekTracker::new(&session, options.seek_threshold));
+
+        let ticker = Ticker::new(options.position_interval, &bus).map(Arc::new);
+        if let Some(ticker) = &ticker {
+            ticker.sync(&session);
+        }
+
+        // whatever is playing already is not a change
+        let track = Arc::new(Mutex::new(None));
+        if let Some(metadata) = cleanup.metadata(&session) {
+            PlayerEvent::track_changed(&track, &metadata);
+        }
+
+        let playback_info_changed_token = session.playback_info_changed(event_handler(
+            &session,
+            &bus,
+            &snapshot,
+            debounce.playback_info,
+            {
+                let seeks = seeks.clone();
+                let ticker = ticker.clone();
+                move |session, bus, snapshot| {
+                    seeks.playback_changed(session);
+                    if let Some(ticker) = &ticker {
+                        ticker.sync(session);
+                    }
+                    let event = PlayerEvent::playback_info_changed(session);
+                    bus.send(event.clone());
+                    snapshot.event_changed(bus, &event);
+                }
+            },
+        ));
+
+        let media_properties_changed_token = session.media_properties_changed(event_handler(
+            &session,
+            &bus,
+            &snapshot,
+            debounce.media_properties,
+            {
+                let cleanup = cleanup.clone();
+                let seeks = seeks.clone();
+                move |session, bus, snapshot| {
+                    let event = PlayerEvent::media_properties_changed(session, &cleanup);
+                    let track_changed = match &event {
+                        PlayerEvent::MediaPropertiesChanged {
+                            metadata: Some(metadata),
+                        } => PlayerEvent::track_changed(&track, metadata),
+                        _ => None,
+                    };
+                    bus.send(event.clone());
+                    if let Some(track_changed) = track_changed {
+                        seeks.track_changed();
+                        bus.send(track_changed);
+                    }
+                    snapshot.event_changed(bus, &event);
+                }
+            },
+        ));
+
+        let timeline_properties_changed_token = session.timeline_properties_changed(event_handler(
+            &session,
+            &bus,
+            &snapshot,
+            debounce.timeline_properties,
+            {
+                let ticker = ticker.clone();
+                move |session, bus, snapshot| {
+                    let seeked = seeks.timeline_changed(session);
+                    if let Some(ticker) = &ticker {
+                        ticker.sync(session);
+                    }
+                    bus.send(PlayerEvent::timeline_properties_changed(session));
+                    if let Some(seeked) = seeked {
+                        bus.send(seeked);
+                    }
+                    snapshot.timeline_changed(bus, session);
+                }
+            },
+        ));
+
+        let event_tokens = EventToken {
+            playback_info_changed_token,
+            media_properties_changed_token,
+            timeline_properties_changed_token,
+        };
+
+        Player {
+            session,
+            aumid,
+
+            options,
+            cleanup,
+
+            bus,
+            events,
+
+            ticker,
+
+            event_tokens,
+        }
+    }
+
+    /// Waits for the next event of this session.
+    pub async fn poll_next_event(&self) -> Option<PlayerEvent> {
+        self.events.recv().await
+    }
+
+    /// A handle to wait for the events of [`Player::poll_next_event`] with,
+    /// so that waiting does not keep the player busy.
+    pub fn get_event_receiver(&self) -> PlayerEventReceiver {
+        self.events.clone()
+    }
+
+    /// A receiver for the events of this session from now on, independent of
+    /// [`Player::poll_next_event`] and of any other subscriber.
+    ///
+    /// Its queue is set up like [`PlayerOptions::event_queue`], see
+    /// [`crate::bus`] for what that means. The receiver ends once this player
+    /// is dropped.
+    pub fn subscribe(&self) -> EventReceiver<PlayerEvent> {
+        self.subscribe_with(self.options.event_queue)
+    }
+
+    /// A handle to subscribe to the events of this session with, so that
+    /// subscribing does not need the player.
+    pub fn get_subscriber(&self) -> PlayerSubscriber {
+        PlayerSubscriber {
+            bus: Arc::downgrade(&self.bus),
+            options: self.options.event_queue,
+        }
+    }
+
+    /// How many events [`Player::poll_next_event`] lost because nobody polled
+    /// them in time.
+    pub fn get_dropped_events(&self) -> u64 {
+        self.events.dropped()
+    }
+
+    /// Same as [`Player::subscribe`], with a queue set up like `options`.
+    pub fn subscribe_with(&self, options: QueueOptions) -> EventReceiver<PlayerEvent> {
+        self.bus.subscribe(options)
+    }
+
+    pub(crate) fn cleanup(&self) -> Arc<MetadataCleanup> {
+        self.cleanup.clone()
+    }
+
+    /// Takes a snapshot of the whole state of this session.
+    pub async fn get_status(&self) -> Status {
+        self.cleanup
+            .status(get_session_status(&self.session, &self.aumid, true))
+    }
+
+    /// The App User Model ID this session belongs to.
+    pub fn get_aumid(&self) -> String {
+        self.aumid.clone()
+    }
+
+    /// Starts playback. Every command returns whether the session accepted it.
+    pub async fn play(&self) -> bool {
+        self.session.try_play().await
+    }
+
+    /// Pauses playback.
+    pub async fn pause(&self) -> bool {
+        self.session.try_pause().await
+    }
+
+    /// Toggles between playing and paused.
+    pub async fn play_pause(&self) -> bool {
+        self.session.try_toggle_play_pause().await
+    }
+
+    /// Stops playback.
+    pub async fn stop(&self) -> bool {
+        self.session.try_stop().await
+    }
+
+    /// The playback status, in the same form as [`Status::status`].
+    pub fn get_playback_status(&self) -> String {
+        get_playback_status(self.session.playback_info().as_ref())
+    }
+
+    /// Skips to the next track.
+    pub async fn next(&self) -> bool {
+        self.session.try_skip_next().await
+    }
+
+    /// Skips to the previous track.
+    pub async fn previous(&self) -> bool {
+        self.session.try_skip_previous().await
+    }
+
+    /// Turns shuffle on or off.
+    pub async fn set_shuffle(&self, value: bool) -> bool {
+        self.session.try_change_shuffle_active(value).await
+    }
+
+    /// Whether shuffle is on.
+    pub fn get_shuffle(&self) -> bool {
+        get_shuffle(self.session.playback_info().as_ref())
+    }
+
+    /// Sets the repeat mode to `None`, `Track` or `List`; anything else means `None`.
+    pub async fn set_repeat(&self, value: String) -> bool {
+        let _val = match value.as_str() {
+            "None" => AutoRepeatMode::None,
+            "List" => AutoRepeatMode::List,
+            "Track" => AutoRepeatMode::Track,
+            _ => AutoRepeatMode::None,
+        };
+        self.session.try_change_auto_repeat_mode(_val).await
+    }
+
+    /// The repeat mode, in the same form as [`Status::is_loop`].
+    pub fn get_repeat(&self) -> String {
+        get_repeat_mode(self.session.playback_info().as_ref())
+    }
+
+    /// Moves the playback position by `offset_s` seconds.
+    pub async fn seek(&self, offset_s: f64) -> bool {
+        if let Some(timeline_properties) = self.session.timeline_properties() {
+            let position = timeline_properties.position.as_secs_f64();
+            return self.set_position(position + offset_s).await;
+        }
+
+        false
+    }
+
+    /// Moves the playback position to a fraction (0 to 1) of the track length.
+    pub async fn seek_percentage(&self, percentage: f64) -> bool {
+        if let Some(timeline_properties) = self.session.timeline_properties() {
+            let start_time = timeline_properties.start_time.as_secs_f64();
+            let end_time = timeline_properties.end_time.as_secs_f64();
+
+            let length = end_time - start_time;
+            return self.set_position(length * percentage).await;
+        }
+        false
+    }
+
+    /// Moves the playback position to `position_s` seconds.
+    pub async fn set_position(&self, position_s: f64) -> bool {
+        self.session
+            .try_change_playback_position((position_s * 1e+7f64) as i64)
+            .await
+    }
+
+    /// The playback position, extrapolated to now if `wants_current_position`.
+    pub async fn get_position(&self, wants_current_position: bool) -> Option<Position> {
+        compute_position(
+            self.session.timeline_properties().as_ref(),
+            self.session.playback_info().as_ref(),
+            wants_current_position,
+        )
+    }
+}
+
+impl<S: MediaSession> Drop for Player<S> {
+    fn drop(&mut self) {
+        if let Some(ticker) = &self.ticker {
+            ticker.stop();
+        }
+        self.session
+            .remove_media_properties_changed(self.event_tokens.media_properties_changed_token);
+        self.session
+            .remove_playback_info_changed(self.event_tokens.playback_info_changed_token);
+        self.session.remove_timeline_properties_changed(
+            self.event_tokens.timeline_properties_changed_token,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use chrono::Utc;
+    use tokio::{sync::Mutex, time::timeout};
+
+    use super::{DebounceOptions, Player, PlayerEvent, PlayerEventReceiver, PlayerOptions};
+    use crate::{
+        backend::{
+            mock::{MockCommand, MockSession},
+            AutoRepeatMode, MediaProperties, PlaybackControls, PlaybackInfo, PlaybackStatus,
+            TimelineProperties,
+        },
+        bus::{OverflowPolicy, QueueOptions},
+        metadata::{ArtistSplitting, NormalizationRules},
+        types::{ArtData, FieldChange, FieldValue},
+    };
+
+    fn session() -> MockSession {
+        let session = MockSession::new("Mock.App");
+        session.set_timeline_properties(Some(TimelineProperties {
+            start_time: Duration::ZERO,
+            end_time: Duration::from_secs(200),
+            position: Duration::from_secs(30),
+            last_updated_time: Utc::now(),
+        }));
+        session
+    }
+
+    // the next event other than StatusChanged, which follows most of them
+    async fn next_event(events: &PlayerEventReceiver) -> Option<PlayerEvent> {
+        loop {
+            match events.recv().await {
+                Some(PlayerEvent::StatusChanged { .. }) => continue,
+                event => return event,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn commands_reach_the_session() {
+        let session = session();
+        let player = Player::new(session.clone(), String::from("Mock.App"));
+
+        assert!(player.play().await);
+        assert!(player.pause().await);
+        assert!(player.play_pause().await);
+        assert!(player.stop().await);
+        assert!(player.next().await);
+        assert!(player.previous().await);
+        assert!(player.set_shuffle(true).await);
+        assert!(player.set_repeat(String::from("List")).await);
+        assert!(player.set_repeat(String::from("whatever")).await);
+        assert!(player.set_position(1.5).await);
+        assert!(player.seek(5f64).await);
+        assert!(player.seek_percentage(0.5).await);
+
+        assert_eq!(
+            session.commands(),
+            vec![
+                MockCommand::Play,
+                MockCommand::Pause,
+                MockCommand::TogglePlayPause,
+                MockCommand::Stop,
+                MockCommand::SkipNext,
+                MockCommand::SkipPrevious,
+                MockCommand::ChangeShuffleActive(true),
+                MockCommand::ChangeAutoRepeatMode(AutoRepeatMode::List),
+                MockCommand::ChangeAutoRepeatMode(AutoRepeatMode::None),
+                MockCommand::ChangePlaybackPosition(15_000_000),
+                MockCommand::ChangePlaybackPosition(350_000_000),
+                MockCommand::ChangePlaybackPosition(1_000_000_000),
+            ]
+        );
+
+        session.clear_commands();
+        session.set_command_result(false);
+        assert!(!player.play().await);
+        assert_eq!(session.commands(), vec![MockCommand::Play]);
+    }
+
+    #[tokio::test]
+    async fn status_reflects_the_session() {
+        let session = session();
+        session.set_playback_info(Some(PlaybackInfo {
+            playback_status: Some(PlaybackStatus::Playing),
+            auto_repeat_mode: Some(AutoRepeatMode::Track),
+            is_shuffle_active: Some(true),
+            controls: Some(PlaybackControls {
+                is_play_enabled: true,
+                is_playback_position_enabled: true,
+                ..Default::default()
+            }),
+        }));
+        session.set_media_properties(Some(MediaProperties {
+            title: String::from("Title"),
+            artist: String::from("Artist"),
+            album_title: Some(String::from("Album")),
+            album_artist: None,
+        }));
+        session.set_thumbnail(Some(ArtData {
+            data: vec![1, 2, 3],
+            mimetype: String::from("image/png"),
+        }));
+        let player = Player::new(session.clone(), String::from("Mock.App"));
+
+        let status = player.get_status().await;
+        assert_eq!(status.status, "Playing");
+        assert_eq!(status.is_loop, "Track");
+        assert!(status.shuffle);
+        assert!(status.capabilities.can_control);
+        assert!(status.capabilities.can_play_pause);
+        assert!(status.capabilities.can_seek);
+        assert!(!status.capabilities.can_go_next);
+        assert_eq!(status.app.as_deref(), Some("Mock.App"));
+        assert_eq!(status.elapsed.unwrap().how_much, 30f64);
+
+        let metadata = status.metadata.unwrap();
+        assert_eq!(metadata.title, "Title");
+        assert_eq!(metadata.artists, vec!["Artist"]);
+        assert_eq!(metadata.album_artists, None);
+        assert_eq!(metadata.length, 200f64);
+        assert_eq!(metadata.art_data.unwrap().data, vec![1, 2, 3]);
+        assert_eq!(
+            metadata.id.as_deref(),
+            Some(format!("{:x}", md5::compute("ArtistAlbumTitle")).as_str())
+        );
+
+        // events leave the art to get_status
+        session.fire_media_properties_changed();
+        match next_event(&player.get_event_receiver()).await {
+            Some(PlayerEvent::MediaPropertiesChanged {
+                metadata: Some(metadata),
+            }) => assert!(metadata.art_data.is_none()),
+            event => panic!("unexpected {:?}", event),
+        }
+    }
+
+    #[tokio::test]
+    async fn events_are_forwarded_until_dropped() {
+        let session = session();
+        let player = Player::new(session.clone(), String::from("Mock.App"));
+        assert_eq!(session.handler_count(), 3);
+
+        session.fire_timeline_properties_changed();
+        session.fire_playback_info_changed();
+        session.fire_media_properties_changed();
+
+        assert!(matches!(
+            player.poll_next_event().await,
+            Some(PlayerEvent::TimelinePropertiesChanged { .. })
+        ));
+        assert!(matches!(
+            player.poll_next_event().await,
+            Some(PlayerEvent::PlaybackInfoChanged { .. })
+        ));
+        assert!(matches!(
+            player.poll_next_event().await,
+            Some(PlayerEvent::MediaPropertiesChanged { .. })
+        ));
+
+        drop(player);
+        assert_eq!(session.handler_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn waiting_for_events_does_not_lock_the_player() {
+        let session = session();
+        // shared the same way PlayerManager hands players out
+        let player = Arc::new(Mutex::new(Player::new(
+            session.clone(),
+            String::from("Mock.App"),
+        )));
+
+        let poll = tokio::spawn({
+            let player = player.clone();
+            async move {
+                let events = player.lock().await.get_event_receiver();
+                events.recv().await
+            }
+        });
+        tokio::task::yield_now().await;
+
+        let status = timeout(Duration::from_secs(1), async {
+            player.lock().await.get_status().await
+        })
+        .await
+        .expect("get_status waited for the pending poll");
+        assert_eq!(status.status, "Stopped");
+        assert!(!poll.is_finished());
+
+        session.fire_playback_info_changed();
+        assert!(matches!(
+            poll.await.unwrap(),
+            Some(PlayerEvent::PlaybackInfoChanged { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn unpolled_events_are_bounded() {
+        let session = session();
+        let options = PlayerOptions {
+            event_queue: QueueOptions {
+                capacity: 2,
+                policy: OverflowPolicy::DropOldest,
+            },
+            ..Default::default()
+        };
+        let player = Player::with_options(session.clone(), String::from("Mock.App"), options);
+
+        for _ in 0..5 {
+            session.fire_timeline_properties_changed();
+        }
+        assert_eq!(player.get_dropped_events(), 3);
+    }
+
+    #[tokio::test]
+    async fn bursts_are_debounced_into_the_final_state() {
+        let session = session();
+        let options = PlayerOptions {
+            debounce: DebounceOptions {
+                media_properties: Some(Duration::from_millis(50)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let player = Player::with_options(session.clone(), String::from("Mock.App"), options);
+        let events = player.get_event_receiver();
+
+        for title in ["", "T", "Ti", "Title"] {
+            session.set_media_properties(Some(MediaProperties {
+                title: String::from(title),
+                artist: String::from("Artist"),
+                album_title: None,
+                album_artist: None,
+            }));
+            session.fire_media_properties_changed();
+            session.fire_timeline_properties_changed();
+        }
+
+        // other kinds are not held back
+        for _ in 0..4 {
+            assert!(matches!(
+                next_event(&events).await,
+                Some(PlayerEvent::TimelinePropertiesChanged { .. })
+            ));
+        }
+
+        match timeout(Duration::from_secs(1), next_event(&events))
+            .await
+            .unwrap()
+        {
+            Some(PlayerEvent::MediaPropertiesChanged { metadata }) => {
+                assert_eq!(metadata.unwrap().title, "Title")
+            }
+            event => panic!("unexpected {:?}", event),
+        }
+        assert!(matches!(
+            next_event(&events).await,
+            Some(PlayerEvent::TrackChanged { .. })
+        ));
+        assert!(matches!(
+            events.recv().await,
+            Some(PlayerEvent::StatusChanged { .. })
+        ));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(events.rx.try_recv().is_none());
+    }
+
+    #[tokio::test]
+    async fn track_changes_follow_the_metadata_id() {
+        let session = session();
+        let set_title = |title: &str| {
+            session.set_media_properties(Some(MediaProperties {
+                title: String::from(title),
+                artist: String::from("Artist"),
+                album_title: None,
+                album_artist: None,
+            }));
+            session.fire_media_properties_changed();
+        };
+        set_title("First");
+        let player = Player::new(session.clone(), String::from("Mock.App"));
+
+        // same track, then the blank one apps report in between
+        set_title("First");
+        set_title("");
+        set_title("Second");
+        set_title("Second");
+
+        let mut events = Vec::new();
+        while let Some(event) = player.events.rx.try_recv() {
+            if !matches!(event, PlayerEvent::StatusChanged { .. }) {
+                events.push(event);
+            }
+        }
+        let track_changes: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                PlayerEvent::TrackChanged { previous, current } => Some((
+                    previous.as_ref().map(|x| x.title.as_str()),
+                    current.title.as_str(),
+                )),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(track_changes, vec![(Some("First"), "Second")]);
+        assert_eq!(events.len(), 5);
+        assert!(matches!(events[3], PlayerEvent::TrackChanged { .. }));
+    }
+
+    #[tokio::test]
+    async fn status_changes_list_the_fields() {
+        let session = session();
+        let player = Player::new(session.clone(), String::from("Mock.App"));
+
+        session.set_playback_info(Some(PlaybackInfo {
+            playback_status: Some(PlaybackStatus::Stopped),
+            auto_repeat_mode: Some(AutoRepeatMode::None),
+            is_shuffle_active: Some(true),
+            controls: Some(PlaybackControls::default()),
+        }));
+        session.fire_playback_info_changed();
+        session.fire_playback_info_changed();
+        session.set_media_properties(Some(MediaProperties {
+            title: String::from("Title"),
+            artist: String::from("Artist"),
+            album_title: None,
+            album_artist: None,
+        }));
+        // only what an event is about is read again
+        session.fire_timeline_properties_changed();
+        session.fire_media_properties_changed();
+
+        let mut changes = Vec::new();
+        while let Some(event) = player.events.rx.try_recv() {
+            if let PlayerEvent::StatusChanged { changes: x } = event {
+                changes.push(x);
+            }
+        }
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            changes[0],
+            vec![FieldChange {
+                field: "shuffle",
+                old: Some(FieldValue::Bool(false)),
+                new: Some(FieldValue::Bool(true)),
+            }]
+        );
+        let fields: Vec<_> = changes[1].iter().map(|x| x.field).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "metadata.artist",
+                "metadata.artists",
+                "metadata.id",
+                "metadata.length",
+                "metadata.title"
+            ]
+        );
+        assert!(changes[1].iter().all(|x| x.old.is_none()));
+        assert_eq!(
+            changes[1][4].new,
+            Some(FieldValue::Text(String::from("Title")))
+        );
+    }
+
+    #[tokio::test]
+    async fn jumps_in_the_position_are_seeks() {
+        let session = session();
+        let player = Player::new(session.clone(), String::from("Mock.App"));
+        let start = Utc::now();
+        let move_to = |position: u64, after: i64, end_time: u64| {
+            session.set_timeline_properties(Some(TimelineProperties {
+                start_time: Duration::ZERO,
+                end_time: Duration::from_secs(end_time),
+                position: Duration::from_secs(position),
+                last_updated_time: start + chrono::Duration::seconds(after),
+            }));
+            session.fire_timeline_properties_changed();
+        };
+
+        // stopped: a second off is imprecision, seventy is a seek
+        move_to(31, 1, 200);
+        move_to(100, 2, 200);
+
+        // playing: the position is expected to move on
+        session.set_playback_status(PlaybackStatus::Playing);
+        session.fire_playback_info_changed();
+        move_to(110, 10, 200);
+
+        // a new track starting from zero
+        move_to(0, 11, 180);
+
+        // the same track over again
+        move_to(179, 190, 180);
+        move_to(0, 191, 180);
+
+        // and one just as long, as told by its metadata
+        move_to(60, 251, 180);
+        session.set_media_properties(Some(MediaProperties {
+            title: String::from("Next"),
+            artist: String::from("Artist"),
+            album_title: None,
+            album_artist: None,
+        }));
+        session.fire_media_properties_changed();
+        move_to(0, 252, 180);
+
+        let mut seeks = Vec::new();
+        while let Some(event) = player.events.rx.try_recv() {
+            if let PlayerEvent::Seeked { from, to } = event {
+                seeks.push((from, to));
+            }
+        }
+        assert_eq!(seeks, vec![(31f64, 100f64)]);
+    }
+
+    #[tokio::test]
+    async fn positions_tick_only_while_playing() {
+        let session = session();
+        let options = PlayerOptions {
+            position_interval: Some(Duration::from_millis(20)),
+            ..Default::default()
+        };
+        let player = Player::with_options(session.clone(), String::from("Mock.App"), options);
+        let events = player.get_event_receiver();
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(events.rx.try_recv().is_none());
+
+        session.set_playback_status(PlaybackStatus::Playing);
+        session.fire_playback_info_changed();
+        let mut ticks = 0;
+        while ticks < 3 {
+            match timeout(Duration::from_secs(1), events.recv())
+                .await
+                .unwrap()
+            {
+                Some(PlayerEvent::PositionTick { position }) => {
+                    assert!(position.how_much >= 30f64);
+                    ticks += 1;
+                }
+                Some(_) => {}
+                None => panic!("the player is gone"),
+            }
+        }
+
+        session.set_playback_status(PlaybackStatus::Paused);
+        session.fire_playback_info_changed();
+        while events.rx.try_recv().is_some() {}
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        while let Some(event) = events.rx.try_recv() {
+            assert!(!matches!(event, PlayerEvent::PositionTick { .. }));
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribers_get_their_own_copy() {
+        let session = session();
+        let player = Player::new(session.clone(), String::from("Mock.App"));
+        let subscriber = player.subscribe();
+
+        session.fire_playback_info_changed();
+        assert!(matches!(
+            player.poll_next_event().await,
+            Some(PlayerEvent::PlaybackInfoChanged { .. })
+        ));
+        assert!(matches!(
+            subscriber.recv().await,
+            Some(PlayerEvent::PlaybackInfoChanged { .. })
+        ));
+
+        drop(player);
+        assert!(subscriber.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn events_carry_the_state_they_fired_with() {
+        let session = session();
+        let player = Player::new(session.clone(), String::from("Mock.App"));
+        let events = player.get_event_receiver();
+
+        session.set_playback_status(PlaybackStatus::Playing);
+        session.fire_playback_info_changed();
+        session.set_playback_status(PlaybackStatus::Paused);
+        session.fire_playback_info_changed();
+
+        session.set_media_properties(Some(MediaProperties {
+            title: String::from("Title"),
+            artist: String::from("Artist"),
+            album_title: None,
+            album_artist: None,
+        }));
+        session.fire_media_properties_changed();
+        session.set_media_properties(None);
+
+        session.fire_timeline_properties_changed();
+        session.set_timeline_properties(None);
+
+        let mut statuses = Vec::new();
+        for _ in 0..2 {
+            match next_event(&events).await {
+                Some(PlayerEvent::PlaybackInfoChanged { status, .. }) => statuses.push(status),
+                event => panic!("unexpected {:?}", event),
+            }
+        }
+        assert_eq!(statuses, vec!["Playing", "Paused"]);
+
+        match next_event(&events).await {
+            Some(PlayerEvent::MediaPropertiesChanged { metadata }) => {
+                assert_eq!(metadata.unwrap().title, "Title")
+            }
+            event => panic!("unexpected {:?}", event),
+        }
+
+        match next_event(&events).await {
+            Some(PlayerEvent::TrackChanged { previous, current }) => {
+                assert!(previous.is_none());
+                assert_eq!(current.title, "Title")
+            }
+            event => panic!("unexpected {:?}", event),
+        }
+
+        match next_event(&events).await {
+            Some(PlayerEvent::TimelinePropertiesChanged { position }) => {
+                assert_eq!(position.unwrap().how_much, 30f64)
+            }
+            event => panic!("unexpected {:?}", event),
+        }
+    }
+
+    #[tokio::test]
+    async fn artists_are_split_as_configured() {
+        let session = session();
+        session.set_media_properties(Some(MediaProperties {
+            title: String::from("Song"),
+            artist: String::from("A & B feat. C"),
+            album_title: None,
+            album_artist: Some(String::from("Simon & Garfunkel, D")),
+        }));
+        let options = PlayerOptions {
+            artist_splitting: ArtistSplitting {
+                protected: vec![String::from("Simon & Garfunkel")],
+                ..ArtistSplitting::common()
+            },
+            ..Default::default()
+        };
+        let player = Player::with_options(session.clone(), String::from("Mock.App"), options);
+
+        let metadata = player.get_status().await.metadata.unwrap();
+        assert_eq!(metadata.artist, "A & B feat. C");
+        assert_eq!(metadata.artists, vec!["A", "B", "C"]);
+        assert_eq!(
+            metadata.album_artists,
+            Some(vec![String::from("Simon & Garfunkel"), String::from("D")])
+        );
+
+        session.fire_media_properties_changed();
+        match next_event(&player.get_event_receiver()).await {
+            Some(PlayerEvent::MediaPropertiesChanged {
+                metadata: Some(metadata),
+            }) => assert_eq!(metadata.artists, vec!["A", "B", "C"]),
+            event => panic!("unexpected {:?}", event),
+        }
+
+        // left whole by default
+        let player = Player::new(session, String::from("Mock.App"));
+        let metadata = player.get_status().await.metadata.unwrap();
+        assert_eq!(metadata.artists, vec!["A & B feat. C"]);
+    }
+
+    #[tokio::test]
+    async fn browser_metadata_is_normalized_before_splitting() {
+        let session = session();
+        session.set_media_properties(Some(MediaProperties {
+            title: String::from("A & B - Song (Official Video)"),
+            artist: String::from("ABVEVO"),
+            album_title: None,
+            album_artist: None,
+        }));
+        let options = PlayerOptions {
+            artist_splitting: ArtistSplitting::common(),
+            normalization: Some(NormalizationRules::common()),
+            ..Default::default()
+        };
+
+        let player = Player::with_options(session.clone(), String::from("Chrome"), options.clone());
+        let metadata = player.get_status().await.metadata.unwrap();
+        assert_eq!(metadata.title, "Song");
+        assert_eq!(metadata.artists, vec!["A", "B"]);
+        assert_eq!(metadata.raw.unwrap().artist, "ABVEVO");
+
+        // the rules are for browsers only
+        let player = Player::with_options(session, String::from("Mock.App"), options);
+        let metadata = player.get_status().await.metadata.unwrap();
+        assert_eq!(metadata.title, "A & B - Song (Official Video)");
+        assert!(metadata.raw.is_none());
+    }
+}
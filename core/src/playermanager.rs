@@ -0,0 +1,1478 @@
+//! Keeps track of all the media sessions and which one is active.
+//!
+//! Each tracked session has an id of its own, since an app can have several
+//! sessions with the same AUMID (e.g. two browser profiles). The id is the
+//! AUMID itself, followed by `#2`, `#3`... when the app already has sessions
+//! with the ids before it, so apps with a single session are known by their
+//! AUMID. Ids only tell apart the sessions tracked at the same time: once a
+//! session goes away, the next one of the app may get its id again.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Weak},
+    time::Duration,
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use tokio::{runtime::Handle, sync::Mutex, task::JoinHandle};
+
+use crate::{
+    backend::{EventRegistrationToken, MediaSession, MediaSessionManager, PlaybackStatus},
+    bus::{EventBus, EventReceiver, OverflowPolicy, QueueOptions},
+    filter::SessionFilter,
+    player::{MetadataCleanup, Player, PlayerEvent, PlayerOptions, PlayerSubscriber},
+    selection::{Candidate, DefaultPolicy, SelectionContext, SelectionPolicy},
+    types::{SessionActivity, Status},
+    util::get_session_status_at,
+};
+
+/// Something changed in the set of sessions.
+#[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
+pub enum ManagerEvent {
+    /// Sessions appeared or disappeared, or the friendly name some filter
+    /// was waiting for is now known; call [`PlayerManager::update_sessions`].
+    SessionsChanged,
+    /// [`PlayerManager::update_sessions`] started tracking a session.
+    SessionAdded {
+        /// Id of the new session.
+        id: String,
+        /// AUMID of the new session.
+        aumid: String,
+    },
+    /// [`PlayerManager::update_sessions`] stopped tracking a session.
+    SessionRemoved {
+        /// Id of the session that is gone.
+        id: String,
+        /// AUMID of the session that is gone.
+        aumid: String,
+    },
+    /// A session went [`PlayerManagerOptions::idle_timeout`] without
+    /// playing, and is hidden until it plays again.
+    SessionExpired {
+        /// Id of the session.
+        id: String,
+        /// AUMID of the session.
+        aumid: String,
+    },
+    /// A session hidden by [`ManagerEvent::SessionExpired`] started playing
+    /// again.
+    SessionRevived {
+        /// Id of the session.
+        id: String,
+        /// AUMID of the session.
+        aumid: String,
+    },
+    /// A different session is now the active one.
+    ActiveSessionChanged {
+        /// Id of the new active session, if there is one.
+        id: Option<String>,
+        /// AUMID of the new active session, if there is one.
+        aumid: Option<String>,
+    },
+    /// The backend's current session changed; call [`PlayerManager::update_system_session`].
+    SystemSessionChanged {
+        /// AUMID of the backend's current session when this fired, if there is one.
+        aumid: Option<String>,
+    },
+}
+
+struct EventToken {
+    sessions_changed_token: EventRegistrationToken,
+    current_session_changed_token: EventRegistrationToken,
+}
+
+/// How a [`PlayerManager`] and the players it creates behave.
+#[derive(Debug, Clone, Default)]
+pub struct PlayerManagerOptions {
+    /// The queue behind [`PlayerManager::poll_next_event`] and the default for
+    /// [`PlayerManager::subscribe`]; the former never blocks, see
+    /// [`OverflowPolicy::Block`].
+    pub event_queue: QueueOptions,
+    /// Options for every [`Player`] the manager creates.
+    pub player: PlayerOptions,
+    /// Whether the manager calls [`PlayerManager::update_sessions`] and
+    /// [`PlayerManager::update_system_session`] by itself whenever the backend
+    /// reports a change, instead of leaving it to whoever gets the events.
+    /// That happens shortly after, on the tokio runtime around when the
+    /// manager was created, if any.
+    pub auto_sync: bool,
+    /// The sessions to track when syncing automatically.
+    pub filter: SessionFilter,
+    /// How long a session can go without playing before it is hidden from
+    /// the selection of the active session and from the listings, until it
+    /// plays again; `None` to keep them all around. Needs a tokio runtime
+    /// around when the manager is created.
+    pub idle_timeout: Option<Duration>,
+}
+
+struct TrackedSession<S: MediaSession> {
+    aumid: String,
+    // tells the backend sessions apart when several share an AUMID
+    session: S,
+    // the order sessions were tracked in
+    added: u64,
+    player: Arc<Mutex<Player<S>>>,
+    // the player's, for statuses read and subscriptions made without it
+    cleanup: Arc<MetadataCleanup>,
+    subscriber: PlayerSubscriber,
+
+    first_seen: DateTime<Utc>,
+    playing: bool,
+    last_playing: Option<DateTime<Utc>>,
+    last_event: Option<DateTime<Utc>>,
+    idle: bool,
+}
+
+impl<S: MediaSession> TrackedSession<S> {
+    fn activity(&self, id: &str, now: DateTime<Utc>) -> SessionActivity {
+        SessionActivity {
+            id: id.to_string(),
+            aumid: self.aumid.clone(),
+            first_seen: self.first_seen,
+            last_playing: if self.playing {
+                Some(now)
+            } else {
+                self.last_playing
+            },
+            last_event: self.last_event,
+        }
+    }
+}
+
+// the part of the manager the backend handlers need to sync on their own
+struct Sessions<M: MediaSessionManager> {
+    this: Weak<std::sync::Mutex<Sessions<M>>>,
+    session_manager: M,
+    player_options: PlayerOptions,
+    // players are usually created from outside of any runtime (JS calling
+    // update_sessions), this is the one they get to debounce on
+    runtime: Option<Handle>,
+    bus: Arc<EventBus<ManagerEvent>>,
+    auto_sync: Option<SessionFilter>,
+    idle_timeout: Option<Duration>,
+
+    friendly_names: HashMap<String, Option<String>>,
+    resolving: HashSet<String>,
+
+    selection_policy: Box<dyn SelectionPolicy>,
+    // an AUMID, kept while its sessions are gone, so that it takes over
+    // again once one is back
+    pinned_aumid: Option<String>,
+
+    active_player_key: Option<String>,
+    // the backend session rather than its id, it may not be tracked yet
+    system_session: Option<M::Session>,
+    players: HashMap<String, TrackedSession<M::Session>>,
+    added: u64,
+
+    // raised while locked, sent by `with_sessions` once unlocked
+    pending: Vec<ManagerEvent>,
+}
+
+// runs `f` on the sessions, then sends the events it raised once they are
+// unlocked: a subscriber that blocks the send must not keep everyone else
+// out of the manager meanwhile
+fn with_sessions<M: MediaSessionManager, T>(
+    sessions: &std::sync::Mutex<Sessions<M>>,
+    f: impl FnOnce(&mut Sessions<M>) -> T,
+) -> T {
+    let (result, bus, events) = {
+        let mut sessions = sessions.lock().unwrap();
+        let result = f(&mut sessions);
+        let events = std::mem::take(&mut sessions.pending);
+        (result, sessions.bus.clone(), events)
+    };
+    for event in events {
+        bus.send(event);
+    }
+    result
+}
+
+// the backend calls its handlers on a thread of its own, maybe while holding
+// locks that whoever has the sessions waits for: syncing right there could
+// deadlock, so it happens on the runtime, if there is one
+fn sync_in_background<M: MediaSessionManager>(
+    runtime: Option<&Handle>,
+    sessions: &Arc<std::sync::Mutex<Sessions<M>>>,
+    sync: fn(&mut Sessions<M>),
+) {
+    match runtime {
+        Some(runtime) => {
+            let sessions = sessions.clone();
+            runtime.spawn_blocking(move || with_sessions(&sessions, sync));
+        }
+        None => with_sessions(sessions, sync),
+    }
+}
+
+impl<M: MediaSessionManager> Sessions<M> {
+    fn sync(&mut self) {
+        if let Some(filter) = self.auto_sync.clone() {
+            self.update_sessions(&filter);
+        }
+    }
+
+    // `None` while the name is being looked up: once it is known, it goes in
+    // the cache and we say so with a SessionsChanged
+    fn friendly_name(&mut self, aumid: &str) -> Option<Option<String>> {
+        if let Some(name) = self.friendly_names.get(aumid) {
+            return Some(name.clone());
+        }
+        let Some(runtime) = &self.runtime else {
+            // nowhere to look it up from
+            self.friendly_names.insert(aumid.to_string(), None);
+            return Some(None);
+        };
+
+        if self.resolving.insert(aumid.to_string()) {
+            let aumid = aumid.to_string();
+            let this = self.this.clone();
+            let session_manager = self.session_manager.clone();
+            runtime.spawn(async move {
+                let name = session_manager.get_friendly_name(&aumid).await;
+                let Some(sessions) = this.upgrade() else {
+                    return;
+                };
+                with_sessions(&sessions, |sessions| {
+                    sessions.resolving.remove(&aumid);
+                    sessions.friendly_names.insert(aumid, name);
+                    sessions.pending.push(ManagerEvent::SessionsChanged);
+                    sessions.sync();
+                });
+            });
+        }
+        None
+    }
+
+    fn update_system_session(&mut self) {
+        if let Some(session) = self.session_manager.get_current_session() {
+            self.system_session = None;
+
+            if let Some(aumid) = session.source_app_user_model_id() {
+                if aumid.is_empty() {
+                    return;
+                }
+
+                self.system_session = Some(session);
+            }
+        }
+    }
+
+    fn id_of(&self, session: &M::Session) -> Option<String> {
+        self.players
+            .iter()
+            .find(|(_, x)| &x.session == session)
+            .map(|(id, _)| id.clone())
+    }
+
+    fn new_id(&self, aumid: &str) -> String {
+        let mut id = aumid.to_string();
+        let mut n = 1;
+        while self.players.contains_key(&id) {
+            n += 1;
+            id = format!("{}#{}", aumid, n);
+        }
+        id
+    }
+
+    fn ids_for(&self, aumid: &str) -> Vec<String> {
+        let mut sessions: Vec<(&String, &TrackedSession<M::Session>)> = self
+            .players
+            .iter()
+            .filter(|(_, x)| x.aumid == aumid && !x.idle)
+            .collect();
+        sessions.sort_by_key(|(_, x)| x.added);
+        sessions.into_iter().map(|(id, _)| id.clone()).collect()
+    }
+
+    fn update_sessions(&mut self, filter: &SessionFilter) {
+        let mut player_keys: Vec<String> = Vec::new();
+        if let Some(sessions) = self.session_manager.get_sessions() {
+            for session in sessions {
+                if let Some(aumid) = session.source_app_user_model_id() {
+                    if aumid.is_empty() {
+                        continue;
+                    }
+
+                    let id = self.id_of(&session);
+
+                    let mut friendly_name = None;
+                    if filter.uses_friendly_names() {
+                        match self.friendly_name(&aumid) {
+                            Some(name) => friendly_name = name,
+                            None => {
+                                // not known yet: we keep it as it is for now
+                                if let Some(id) = id {
+                                    player_keys.push(id);
+                                }
+                                continue;
+                            }
+                        }
+                    }
+
+                    if !filter.accepts(&aumid, friendly_name.as_deref()) {
+                        continue;
+                    }
+
+                    if let Some(id) = id {
+                        player_keys.push(id);
+                        continue;
+                    }
+
+                    let id = self.new_id(&aumid);
+                    player_keys.push(id.clone());
+
+                    let _runtime = self.runtime.as_ref().map(Handle::enter);
+                    let player = Player::with_options(
+                        session.clone(),
+                        aumid.clone(),
+                        self.player_options.clone(),
+                    );
+                    self.added += 1;
+                    self.watch_player(&player, &id, self.added);
+                    let cleanup = player.cleanup();
+                    let subscriber = player.get_subscriber();
+                    let now = Utc::now();
+                    let playing = session.playback_info().and_then(|x| x.playback_status)
+                        == Some(PlaybackStatus::Playing);
+                    self.players.insert(
+                        id.clone(),
+                        TrackedSession {
+                            aumid: aumid.clone(),
+                            session,
+                            added: self.added,
+                            player: Arc::new(Mutex::new(player)),
+                            cleanup,
+                            subscriber,
+
+                            first_seen: now,
+                            playing,
+                            last_playing: playing.then_some(now),
+                            last_event: None,
+                            idle: false,
+                        },
+                    );
+                    self.pending.push(ManagerEvent::SessionAdded { id, aumid });
+                }
+            }
+
+            let removed: Vec<String> = self
+                .players
+                .keys()
+                .filter(|x| !player_keys.contains(x))
+                .cloned()
+                .collect();
+            for id in removed {
+                if let Some(tracked) = self.players.remove(&id) {
+                    self.pending.push(ManagerEvent::SessionRemoved {
+                        id,
+                        aumid: tracked.aumid,
+                    });
+                }
+            }
+
+            self.update_active_player();
+        }
+    }
+
+    // keeps the activity of the session up to date, and since the policy
+    // may want another session once playback starts or stops somewhere, we
+    // ask it again then
+    fn watch_player(&self, player: &Player<M::Session>, id: &str, added: u64) {
+        let Some(runtime) = &self.runtime else {
+            return;
+        };
+        // one of each kind waiting is all we need
+        let rx = player.subscribe_with(QueueOptions {
+            capacity: 16,
+            policy: OverflowPolicy::Coalesce,
+        });
+        let this = self.this.clone();
+        let id = id.to_string();
+        runtime.spawn(async move {
+            while let Some(event) = rx.recv().await {
+                if let PlayerEvent::PositionTick { .. } = event {
+                    continue;
+                }
+                let Some(sessions) = this.upgrade() else {
+                    return;
+                };
+                let still_tracked = with_sessions(&sessions, |sessions| {
+                    // the id may belong to a newer session by now
+                    let Some(tracked) = sessions.players.get_mut(&id).filter(|x| x.added == added)
+                    else {
+                        return false;
+                    };
+
+                    let now = Utc::now();
+                    tracked.last_event = Some(now);
+                    if let PlayerEvent::PlaybackInfoChanged { status, .. } = &event {
+                        let playing = status == "Playing";
+                        if playing || tracked.playing {
+                            tracked.last_playing = Some(now);
+                        }
+                        tracked.playing = playing;
+                        if playing && tracked.idle {
+                            tracked.idle = false;
+                            let aumid = tracked.aumid.clone();
+                            sessions.pending.push(ManagerEvent::SessionRevived {
+                                id: id.clone(),
+                                aumid,
+                            });
+                        }
+                        sessions.update_active_player();
+                    }
+                    true
+                });
+                if !still_tracked {
+                    return;
+                }
+            }
+        });
+    }
+
+    fn expire_idle(&mut self) {
+        let Some(timeout) = self.idle_timeout.and_then(|x| TimeDelta::from_std(x).ok()) else {
+            return;
+        };
+        let now = Utc::now();
+
+        let mut expired = false;
+        for (id, tracked) in self.players.iter_mut() {
+            let since = tracked.last_playing.unwrap_or(tracked.first_seen);
+            if tracked.idle || tracked.playing || now - since < timeout {
+                continue;
+            }
+            tracked.idle = true;
+            expired = true;
+            self.pending.push(ManagerEvent::SessionExpired {
+                id: id.clone(),
+                aumid: tracked.aumid.clone(),
+            });
+        }
+        if expired {
+            self.update_active_player();
+        }
+    }
+
+    fn update_active_player(&mut self) {
+        if let Some(sessions) = self.session_manager.get_sessions() {
+            let candidates: Vec<Candidate> = sessions
+                .into_iter()
+                .filter_map(|s| {
+                    let id = self.id_of(&s).filter(|x| !self.players[x].idle)?;
+                    Some(Candidate {
+                        aumid: self.players[&id].aumid.clone(),
+                        id,
+                        playback_status: s.playback_info().and_then(|x| x.playback_status),
+                    })
+                })
+                .collect();
+            let system = self.system_session.as_ref().and_then(|x| self.id_of(x));
+
+            // the policy still sees every change, so it is up to date once
+            // the pinned sessions go away
+            let mut new = self
+                .selection_policy
+                .select(&SelectionContext {
+                    current: self.active_player_key.as_deref(),
+                    system: system.as_deref(),
+                    candidates: &candidates,
+                })
+                .filter(|id| candidates.iter().any(|x| &x.id == id));
+            if let Some(pinned) = &self.pinned_aumid {
+                let is_pinned = |id: &Option<String>| {
+                    id.as_ref()
+                        .is_some_and(|x| &self.players[x].aumid == pinned)
+                };
+                if !is_pinned(&new) {
+                    // of several sessions of the app, we stay on the one we are on
+                    let current = self
+                        .active_player_key
+                        .clone()
+                        .filter(|x| candidates.iter().any(|c| &c.id == x));
+                    if is_pinned(&current) {
+                        new = current;
+                    } else if let Some(id) = self
+                        .ids_for(pinned)
+                        .into_iter()
+                        .find(|id| candidates.iter().any(|x| &x.id == id))
+                    {
+                        new = Some(id);
+                    }
+                }
+            }
+
+            // we need to arrive here so we cannot return early
+            if self.active_player_key != new {
+                self.active_player_key = new.clone();
+                let aumid = new.as_ref().map(|x| self.players[x].aumid.clone());
+                self.pending
+                    .push(ManagerEvent::ActiveSessionChanged { id: new, aumid });
+            }
+        }
+    }
+}
+
+/// Owns a [`Player`] for every session of a backend, keyed by session id.
+pub struct PlayerManager<M: MediaSessionManager> {
+    session_manager: M,
+    options: PlayerManagerOptions,
+
+    sessions: Arc<std::sync::Mutex<Sessions<M>>>,
+
+    bus: Arc<EventBus<ManagerEvent>>,
+    rx: EventReceiver<ManagerEvent>,
+
+    event_tokens: EventToken,
+    expiry_task: Option<JoinHandle<()>>,
+}
+
+impl<M: MediaSessionManager> PlayerManager<M> {
+    /// Requests the session manager of the backend, if it is available.
+    pub async fn new() -> Option<Self> {
+        Self::with_options(PlayerManagerOptions::default()).await
+    }
+
+    /// Same as [`PlayerManager::new`], with something other than the default options.
+    pub async fn with_options(options: PlayerManagerOptions) -> Option<Self> {
+        Some(Self::from_session_manager_with_options(
+            M::request().await?,
+            options,
+        ))
+    }
+
+    /// Wraps an already obtained session manager.
+    pub fn from_session_manager(session_manager: M) -> Self {
+        Self::from_session_manager_with_options(session_manager, PlayerManagerOptions::default())
+    }
+
+    /// Same as [`PlayerManager::from_session_manager`], with something other
+    /// than the default options.
+    pub fn from_session_manager_with_options(
+        session_manager: M,
+        options: PlayerManagerOptions,
+    ) -> Self {
+        let bus = Arc::new(EventBus::new());
+        let rx = bus.subscribe(options.event_queue.without_blocking());
+
+        let sessions = Arc::new_cyclic(|this| {
+            std::sync::Mutex::new(Sessions {
+                this: this.clone(),
+                session_manager: session_manager.clone(),
+                player_options: options.player.clone(),
+                runtime: Handle::try_current().ok(),
+                bus: bus.clone(),
+                auto_sync: options.auto_sync.then(|| options.filter.clone()),
+                idle_timeout: options.idle_timeout,
+
+                friendly_names: HashMap::new(),
+                resolving: HashSet::new(),
+
+                selection_policy: Box::new(DefaultPolicy),
+                pinned_aumid: None,
+
+                players: HashMap::new(),
+                added: 0,
+                active_player_key: None,
+                system_session: None,
+
+                pending: Vec::new(),
+            })
+        });
+
+        // the handlers hold on to the session manager through `sessions`, we
+        // break the cycle by unregistering them on drop
+        let runtime = Handle::try_current().ok();
+        let sessions_changed_token = session_manager.sessions_changed(Box::new({
+            let bus = bus.clone();
+            let sessions = sessions.clone();
+            let runtime = runtime.clone();
+            let auto_sync = options.auto_sync;
+            move || {
+                bus.send(ManagerEvent::SessionsChanged);
+                if auto_sync {
+                    sync_in_background(runtime.as_ref(), &sessions, Sessions::sync);
+                }
+            }
+        }));
+
+        let current_session_changed_token = session_manager.current_session_changed(Box::new({
+            let bus = bus.clone();
+            let sessions = sessions.clone();
+            let session_manager = session_manager.clone();
+            let runtime = runtime.clone();
+            let auto_sync = options.auto_sync;
+            move || {
+                let aumid = session_manager
+                    .get_current_session()
+                    .and_then(|x| x.source_app_user_model_id())
+                    .filter(|x| !x.is_empty());
+                bus.send(ManagerEvent::SystemSessionChanged { aumid });
+                if auto_sync {
+                    sync_in_background(
+                        runtime.as_ref(),
+                        &sessions,
+                        Sessions::update_system_session,
+                    );
+                }
+            }
+        }));
+
+        let event_tokens = EventToken {
+            sessions_changed_token,
+            current_session_changed_token,
+        };
+
+        bus.send(ManagerEvent::SessionsChanged);
+        if options.auto_sync {
+            with_sessions(&sessions, |sessions| {
+                sessions.update_system_session();
+                sessions.sync();
+            });
+        }
+
+        // expiring a few tenths of a second late is fine, so we simply look
+        // every now and then
+        let expiry_task = options.idle_timeout.zip(runtime).map(|(timeout, runtime)| {
+            let sessions = Arc::downgrade(&sessions);
+            runtime.spawn(async move {
+                let mut interval = tokio::time::interval(timeout.min(Duration::from_secs(1)));
+                loop {
+                    interval.tick().await;
+                    let Some(sessions) = sessions.upgrade() else {
+                        return;
+                    };
+                    with_sessions(&sessions, Sessions::expire_idle);
+                }
+            })
+        });
+
+        PlayerManager {
+            session_manager,
+            options,
+
+            sessions,
+
+            bus,
+            rx,
+
+            event_tokens,
+            expiry_task,
+        }
+    }
+
+    /// Waits for the next event of the manager.
+    pub async fn poll_next_event(&mut self) -> Option<ManagerEvent> {
+        self.rx.recv().await
+    }
+
+    /// A receiver for the events of the manager from now on, independent of
+    /// [`PlayerManager::poll_next_event`] and of any other subscriber.
+    ///
+    /// Its queue is set up like [`PlayerManagerOptions::event_queue`], see
+    /// [`crate::bus`] for what that means. The receiver ends once this
+    /// manager is dropped.
+    pub fn subscribe(&self) -> EventReceiver<ManagerEvent> {
+        self.subscribe_with(self.options.event_queue)
+    }
+
+    /// Same as [`PlayerManager::subscribe`], with a queue set up like `options`.
+    pub fn subscribe_with(&self, options: QueueOptions) -> EventReceiver<ManagerEvent> {
+        self.bus.subscribe(options)
+    }
+
+    /// How many events [`PlayerManager::poll_next_event`] lost because nobody
+    /// polled them in time.
+    pub fn get_dropped_events(&self) -> u64 {
+        self.rx.dropped()
+    }
+
+    /// The session that is currently considered active.
+    pub fn get_active_session(&self) -> Option<Arc<Mutex<Player<M::Session>>>> {
+        let sessions = self.sessions.lock().unwrap();
+        if let Some(player_key) = &sessions.active_player_key {
+            return Some(sessions.players.get(player_key)?.player.clone());
+        }
+        None
+    }
+
+    /// The id of the session that is currently considered active.
+    pub fn get_active_session_id(&self) -> Option<String> {
+        self.sessions.lock().unwrap().active_player_key.clone()
+    }
+
+    /// The session with the id `id`; for an app with a single session, that
+    /// is its AUMID.
+    pub fn get_session(&self, id: &String) -> Option<Arc<Mutex<Player<M::Session>>>> {
+        Some(
+            self.sessions
+                .lock()
+                .unwrap()
+                .players
+                .get(id)?
+                .player
+                .clone(),
+        )
+    }
+
+    /// The [`PlayerSubscriber`] of `player`, one of the sessions handed out
+    /// by this manager, to subscribe to it without waiting for whoever holds
+    /// it; `None` once it is no longer tracked.
+    pub fn get_subscriber(
+        &self,
+        player: &Arc<Mutex<Player<M::Session>>>,
+    ) -> Option<PlayerSubscriber> {
+        let sessions = self.sessions.lock().unwrap();
+        sessions
+            .players
+            .values()
+            .find(|x| Arc::ptr_eq(&x.player, player))
+            .map(|x| x.subscriber.clone())
+    }
+
+    /// The ids of the sessions of `aumid`, in the order they were tracked.
+    pub fn get_session_ids_for(&self, aumid: &str) -> Vec<String> {
+        self.sessions.lock().unwrap().ids_for(aumid)
+    }
+
+    /// The sessions of `aumid`, in the order they were tracked.
+    pub fn get_sessions_for(&self, aumid: &str) -> Vec<Arc<Mutex<Player<M::Session>>>> {
+        let sessions = self.sessions.lock().unwrap();
+        sessions
+            .ids_for(aumid)
+            .iter()
+            .map(|id| sessions.players[id].player.clone())
+            .collect()
+    }
+
+    /// The ids of all the tracked sessions, in the order they were tracked.
+    ///
+    /// Like the other listings, it leaves out the sessions expired by
+    /// [`PlayerManagerOptions::idle_timeout`]; [`PlayerManager::get_session`]
+    /// still finds them.
+    pub fn get_sessions_keys(&self) -> Vec<String> {
+        let sessions = self.sessions.lock().unwrap();
+        let mut keys: Vec<&String> = sessions
+            .players
+            .iter()
+            .filter(|(_, x)| !x.idle)
+            .map(|(id, _)| id)
+            .collect();
+        keys.sort_by_key(|x| sessions.players[*x].added);
+        keys.into_iter().map(String::from).collect::<Vec<String>>()
+    }
+
+    /// The activity of every tracked session, the most recently playing
+    /// first; sessions that never played come last, the newest first.
+    pub fn get_sessions_by_recency(&self) -> Vec<SessionActivity> {
+        let sessions = self.sessions.lock().unwrap();
+        let now = Utc::now();
+        let mut activities: Vec<(u64, SessionActivity)> = sessions
+            .players
+            .iter()
+            .filter(|(_, x)| !x.idle)
+            .map(|(id, x)| (x.added, x.activity(id, now)))
+            .collect();
+        // ties, e.g. several sessions playing right now, go to the newest
+        activities.sort_by(|(a_added, a), (b_added, b)| {
+            (b.last_playing, b_added).cmp(&(a.last_playing, a_added))
+        });
+        activities.into_iter().map(|(_, x)| x).collect()
+    }
+
+    /// The status of every tracked session, keyed by id, cover art included
+    /// only if `with_art` is set.
+    ///
+    /// All the positions are as of the same instant, extrapolated for the
+    /// sessions that are playing. Expired sessions are left out, like in the
+    /// other listings.
+    pub fn get_all_statuses(&self, with_art: bool) -> HashMap<String, Status> {
+        // reading the sessions can take a while, the art above all, and it
+        // needs nothing from the manager
+        let tracked: Vec<_> = {
+            let sessions = self.sessions.lock().unwrap();
+            sessions
+                .players
+                .iter()
+                .filter(|(_, x)| !x.idle)
+                .map(|(id, x)| {
+                    (
+                        id.clone(),
+                        x.session.clone(),
+                        x.aumid.clone(),
+                        x.cleanup.clone(),
+                    )
+                })
+                .collect()
+        };
+        let now = Utc::now();
+        tracked
+            .into_iter()
+            .map(|(id, session, aumid, cleanup)| {
+                let status = get_session_status_at(&session, &aumid, with_art, now);
+                (id, cleanup.status(status))
+            })
+            .collect()
+    }
+
+    /// The session the backend considers current, if we track it.
+    pub fn get_system_session(&self) -> Option<Arc<Mutex<Player<M::Session>>>> {
+        let sessions = self.sessions.lock().unwrap();
+        if let Some(session) = &sessions.system_session {
+            let id = sessions.id_of(session)?;
+            return Some(sessions.players[&id].player.clone());
+        }
+        None
+    }
+
+    /// Changes how the active session is picked, and picks it again right
+    /// away; [`DefaultPolicy`] until then.
+    pub fn set_selection_policy(&mut self, policy: impl SelectionPolicy + 'static) {
+        with_sessions(&self.sessions, |sessions| {
+            sessions.selection_policy = Box::new(policy);
+            sessions.update_active_player();
+        });
+    }
+
+    /// Makes a session of `aumid` the active one whatever the selection
+    /// policy says, for as long as one is tracked: the one the policy picks
+    /// if it picks one of them, else the active one if it is one of them,
+    /// else the first one tracked that the backend still reports and that
+    /// has not expired.
+    ///
+    /// The pin outlives the sessions: if they go away, the policy picks until
+    /// a session of `aumid` is tracked again, which then becomes active again.
+    pub fn pin_session(&mut self, aumid: &str) {
+        with_sessions(&self.sessions, |sessions| {
+            sessions.pinned_aumid = Some(aumid.to_string());
+            sessions.update_active_player();
+        });
+    }
+
+    /// Leaves picking the active session to the selection policy again.
+    pub fn unpin_session(&mut self) {
+        with_sessions(&self.sessions, |sessions| {
+            sessions.pinned_aumid = None;
+            sessions.update_active_player();
+        });
+    }
+
+    /// The AUMID given to [`PlayerManager::pin_session`], if any, whether its
+    /// sessions are tracked or not.
+    pub fn get_pinned_session(&self) -> Option<String> {
+        self.sessions.lock().unwrap().pinned_aumid.clone()
+    }
+
+    /// Refreshes which session the backend considers current.
+    ///
+    /// With [`PlayerManagerOptions::auto_sync`] this already happens on its own.
+    pub fn update_system_session(&mut self) {
+        self.sessions.lock().unwrap().update_system_session()
+    }
+
+    /// Reconciles the tracked sessions with the backend, skipping AUMIDs in
+    /// `denylist`, then picks the active session again.
+    ///
+    /// Every session it starts or stops tracking is announced with a
+    /// [`ManagerEvent::SessionAdded`] or [`ManagerEvent::SessionRemoved`],
+    /// before any [`ManagerEvent::ActiveSessionChanged`]. With
+    /// [`PlayerManagerOptions::auto_sync`] this already happens on its own,
+    /// with [`PlayerManagerOptions::filter`].
+    pub fn update_sessions(&mut self, denylist: Option<&Vec<String>>) {
+        let filter = denylist.map_or_else(SessionFilter::default, |x| SessionFilter::denylist(x));
+        self.update_sessions_with(&filter)
+    }
+
+    /// Same as [`PlayerManager::update_sessions`], tracking the sessions
+    /// `filter` accepts.
+    ///
+    /// If `filter` needs the friendly name of an app that is not known yet,
+    /// its session is left as it is until the name is looked up, which
+    /// happens in the background and ends with a
+    /// [`ManagerEvent::SessionsChanged`]. Without a tokio runtime around when
+    /// the manager was created, names cannot be looked up and are taken to be
+    /// missing.
+    pub fn update_sessions_with(&mut self, filter: &SessionFilter) {
+        with_sessions(&self.sessions, |sessions| sessions.update_sessions(filter))
+    }
+}
+
+impl<M: MediaSessionManager> Drop for PlayerManager<M> {
+    fn drop(&mut self) {
+        self.session_manager
+            .remove_sessions_changed(self.event_tokens.sessions_changed_token);
+        self.session_manager
+            .remove_current_session_changed(self.event_tokens.current_session_changed_token);
+        if let Some(task) = self.expiry_task.take() {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::Utc;
+    use tokio::time::timeout;
+
+    use super::{ManagerEvent, PlayerManager, PlayerManagerOptions};
+    use crate::{
+        backend::{mock::MockSessionManager, MediaProperties, PlaybackStatus, TimelineProperties},
+        bus::{OverflowPolicy, QueueOptions},
+        filter::{Pattern, SessionFilter, SessionMatcher},
+        selection::{MostRecentlyPlaying, SelectionContext, SelectionPolicy},
+        types::ArtData,
+    };
+
+    fn sorted_keys(player_manager: &PlayerManager<MockSessionManager>) -> Vec<String> {
+        let mut keys = player_manager.get_sessions_keys();
+        keys.sort();
+        keys
+    }
+
+    async fn active_aumid(player_manager: &PlayerManager<MockSessionManager>) -> Option<String> {
+        Some(
+            player_manager
+                .get_active_session()?
+                .lock()
+                .await
+                .get_aumid(),
+        )
+    }
+
+    fn describe(event: ManagerEvent) -> String {
+        match event {
+            ManagerEvent::SessionsChanged => String::from("SessionsChanged"),
+            ManagerEvent::SessionAdded { id, .. } => format!("SessionAdded({})", id),
+            ManagerEvent::SessionRemoved { id, .. } => format!("SessionRemoved({})", id),
+            ManagerEvent::SessionExpired { id, .. } => format!("SessionExpired({})", id),
+            ManagerEvent::SessionRevived { id, .. } => format!("SessionRevived({})", id),
+            ManagerEvent::ActiveSessionChanged { id, .. } => {
+                format!("ActiveSessionChanged({})", id.unwrap_or_default())
+            }
+            ManagerEvent::SystemSessionChanged { aumid } => {
+                format!("SystemSessionChanged({})", aumid.unwrap_or_default())
+            }
+        }
+    }
+
+    fn drain_events(player_manager: &mut PlayerManager<MockSessionManager>) -> Vec<String> {
+        let mut events = Vec::new();
+        while let Some(event) = player_manager.rx.try_recv() {
+            events.push(describe(event));
+        }
+        events
+    }
+
+    // for what happens off the test's task: the events up to `last`, which
+    // are sent once the change is done
+    async fn events_until(
+        player_manager: &mut PlayerManager<MockSessionManager>,
+        last: &str,
+    ) -> Vec<String> {
+        let mut events = Vec::new();
+        while events.last().map(String::as_str) != Some(last) {
+            let event = timeout(Duration::from_secs(5), player_manager.poll_next_event())
+                .await
+                .unwrap()
+                .unwrap();
+            events.push(describe(event));
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn update_sessions_reconciles_players() {
+        let session_manager = MockSessionManager::new();
+        let a_session = session_manager.add_session("A");
+        session_manager.add_session("B");
+        session_manager.add_session("");
+        let mut player_manager = PlayerManager::from_session_manager(session_manager.clone());
+
+        player_manager.update_sessions(None);
+        assert_eq!(sorted_keys(&player_manager), vec!["A", "B"]);
+
+        let denylist = vec![String::from("B")];
+        player_manager.update_sessions(Some(&denylist));
+        assert_eq!(sorted_keys(&player_manager), vec!["A"]);
+        assert!(drain_events(&mut player_manager).contains(&String::from("SessionRemoved(B)")));
+
+        let a = player_manager.get_session(&String::from("A")).unwrap();
+        session_manager.remove_session("B");
+        session_manager.add_session("C");
+        player_manager.update_sessions(None);
+        assert_eq!(sorted_keys(&player_manager), vec!["A", "C"]);
+        // players that are still around are kept, not recreated
+        assert!(std::sync::Arc::ptr_eq(
+            &a,
+            &player_manager.get_session(&String::from("A")).unwrap()
+        ));
+
+        // subscribing does not wait for whoever holds the player
+        let held = a.lock().await;
+        let rx = player_manager.get_subscriber(&a).unwrap().subscribe();
+        a_session.fire_playback_info_changed();
+        assert!(rx.try_recv().is_some());
+        drop(held);
+
+        session_manager.remove_session("A");
+        player_manager.update_sessions(None);
+        assert_eq!(sorted_keys(&player_manager), vec!["C"]);
+        assert!(player_manager.get_subscriber(&a).is_none());
+    }
+
+    #[tokio::test]
+    async fn update_active_player_follows_priority() {
+        let session_manager = MockSessionManager::new();
+        let a = session_manager.add_session("A");
+        let b = session_manager.add_session("B");
+        let c = session_manager.add_session("C");
+        a.set_playback_status(PlaybackStatus::Paused);
+        b.set_playback_status(PlaybackStatus::Playing);
+        c.set_playback_status(PlaybackStatus::Stopped);
+        let mut player_manager = PlayerManager::from_session_manager(session_manager.clone());
+
+        // Playing beats others
+        player_manager.update_sessions(None);
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("B"));
+
+        // the previously active session beats the system one
+        session_manager.set_current_session(Some(&c));
+        player_manager.update_system_session();
+        player_manager.update_sessions(None);
+        assert_eq!(
+            player_manager
+                .get_system_session()
+                .unwrap()
+                .lock()
+                .await
+                .get_aumid(),
+            "C"
+        );
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("B"));
+
+        // and the system session beats playing ones
+        session_manager.remove_session("B");
+        session_manager
+            .add_session("D")
+            .set_playback_status(PlaybackStatus::Playing);
+        player_manager.update_sessions(None);
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("C"));
+
+        session_manager.remove_session("C");
+        session_manager.remove_session("D");
+        player_manager.update_sessions(None);
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("A"));
+
+        session_manager.remove_session("A");
+        player_manager.update_sessions(None);
+        assert!(player_manager.get_active_session().is_none());
+    }
+
+    #[tokio::test]
+    async fn backend_events_are_forwarded() {
+        let session_manager = MockSessionManager::new();
+        let a = session_manager.add_session("A");
+        a.set_playback_status(PlaybackStatus::Playing);
+        let mut player_manager = PlayerManager::from_session_manager(session_manager.clone());
+        assert_eq!(drain_events(&mut player_manager), vec!["SessionsChanged"]);
+
+        session_manager.fire_sessions_changed();
+        session_manager.fire_current_session_changed();
+        session_manager.set_current_session(Some(&a));
+        session_manager.fire_current_session_changed();
+        assert_eq!(
+            drain_events(&mut player_manager),
+            vec![
+                "SessionsChanged",
+                "SystemSessionChanged()",
+                "SystemSessionChanged(A)"
+            ]
+        );
+
+        player_manager.update_sessions(None);
+        player_manager.update_sessions(None);
+        assert_eq!(
+            drain_events(&mut player_manager),
+            vec!["SessionAdded(A)", "ActiveSessionChanged(A)"]
+        );
+
+        session_manager.remove_session("A");
+        player_manager.update_sessions(None);
+        assert_eq!(
+            drain_events(&mut player_manager),
+            vec!["SessionRemoved(A)", "ActiveSessionChanged()"]
+        );
+
+        drop(player_manager);
+        session_manager.fire_sessions_changed();
+    }
+
+    #[tokio::test]
+    async fn unpolled_events_never_block_the_manager() {
+        let session_manager = MockSessionManager::new();
+        session_manager.add_session("A");
+        session_manager.add_session("B");
+        let options = PlayerManagerOptions {
+            event_queue: QueueOptions {
+                capacity: 1,
+                policy: OverflowPolicy::Block,
+            },
+            ..Default::default()
+        };
+        let mut player_manager =
+            PlayerManager::from_session_manager_with_options(session_manager, options);
+
+        player_manager.update_sessions(None);
+        assert_eq!(sorted_keys(&player_manager), vec!["A", "B"]);
+        assert!(player_manager.get_dropped_events() > 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn auto_sync_follows_the_backend() {
+        let session_manager = MockSessionManager::new();
+        let a = session_manager.add_session("A");
+        session_manager.add_session("Denied");
+        session_manager.set_current_session(Some(&a));
+        let options = PlayerManagerOptions {
+            auto_sync: true,
+            filter: SessionFilter::denylist(&[String::from("Denied")]),
+            ..Default::default()
+        };
+        let mut player_manager =
+            PlayerManager::from_session_manager_with_options(session_manager.clone(), options);
+        assert_eq!(sorted_keys(&player_manager), vec!["A"]);
+        assert!(player_manager.get_system_session().is_some());
+        assert_eq!(
+            drain_events(&mut player_manager),
+            vec![
+                "SessionsChanged",
+                "SessionAdded(A)",
+                "ActiveSessionChanged(A)"
+            ]
+        );
+
+        let b = session_manager.add_session("B");
+        session_manager.fire_sessions_changed();
+        assert_eq!(
+            events_until(&mut player_manager, "SessionAdded(B)").await,
+            vec!["SessionsChanged", "SessionAdded(B)"]
+        );
+        assert_eq!(sorted_keys(&player_manager), vec!["A", "B"]);
+
+        session_manager.set_current_session(Some(&b));
+        session_manager.fire_current_session_changed();
+        // the event goes out before the update, which says nothing; with the
+        // clock paused, sleeping waits for the blocking task doing it
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(
+            player_manager
+                .get_system_session()
+                .unwrap()
+                .lock()
+                .await
+                .get_aumid(),
+            "B"
+        );
+
+        session_manager.remove_session("A");
+        session_manager.fire_sessions_changed();
+        assert_eq!(
+            events_until(&mut player_manager, "ActiveSessionChanged(B)").await,
+            vec![
+                "SystemSessionChanged(B)",
+                "SessionsChanged",
+                "SessionRemoved(A)",
+                "ActiveSessionChanged(B)"
+            ]
+        );
+        assert_eq!(sorted_keys(&player_manager), vec!["B"]);
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("B"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn friendly_name_filters_wait_for_the_names() {
+        let session_manager = MockSessionManager::new();
+        session_manager.add_session("Spotify");
+        session_manager.add_session("chrome.exe");
+        session_manager.add_session("vlc");
+        let mut player_manager = PlayerManager::from_session_manager(session_manager.clone());
+        drain_events(&mut player_manager);
+
+        // the mock names apps after their AUMID
+        let filter = SessionFilter {
+            allow: vec![
+                SessionMatcher::FriendlyName(Pattern::exact("Spotify")),
+                SessionMatcher::Aumid(Pattern::glob("*CHROME*")),
+            ],
+            deny: Vec::new(),
+        };
+        player_manager.update_sessions_with(&filter);
+        assert!(sorted_keys(&player_manager).is_empty());
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let events = drain_events(&mut player_manager);
+        assert!(events.iter().all(|x| x == "SessionsChanged"));
+        assert!(!events.is_empty());
+
+        player_manager.update_sessions_with(&filter);
+        assert_eq!(sorted_keys(&player_manager), vec!["Spotify", "chrome.exe"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn selection_policies_pick_the_active_session() {
+        let session_manager = MockSessionManager::new();
+        let a = session_manager.add_session("A");
+        let b = session_manager.add_session("B");
+        a.set_playback_status(PlaybackStatus::Playing);
+        let mut player_manager = PlayerManager::from_session_manager(session_manager.clone());
+        player_manager.update_sessions(None);
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("A"));
+
+        struct Last;
+        impl SelectionPolicy for Last {
+            fn select(&mut self, context: &SelectionContext) -> Option<String> {
+                Some(context.candidates.last()?.id.clone())
+            }
+        }
+        player_manager.set_selection_policy(Last);
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("B"));
+
+        // playback changes are enough to pick again
+        player_manager.set_selection_policy(MostRecentlyPlaying::default());
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("A"));
+        b.set_playback_status(PlaybackStatus::Playing);
+        b.fire_playback_info_changed();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("B"));
+    }
+
+    #[tokio::test]
+    async fn pinned_sessions_survive_going_away() {
+        let session_manager = MockSessionManager::new();
+        session_manager.add_session("A");
+        session_manager
+            .add_session("B")
+            .set_playback_status(PlaybackStatus::Playing);
+        let mut player_manager = PlayerManager::from_session_manager(session_manager.clone());
+        player_manager.update_sessions(None);
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("B"));
+
+        player_manager.pin_session("A");
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("A"));
+        session_manager
+            .add_session("C")
+            .set_playback_status(PlaybackStatus::Playing);
+        player_manager.update_sessions(None);
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("A"));
+
+        // gone from the backend, if not untracked yet, it is no candidate
+        session_manager.remove_session("A");
+        player_manager.pin_session("A");
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("B"));
+
+        player_manager.update_sessions(None);
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("B"));
+        assert_eq!(player_manager.get_pinned_session().as_deref(), Some("A"));
+
+        session_manager.add_session("A");
+        player_manager.update_sessions(None);
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("A"));
+
+        player_manager.unpin_session();
+        assert_eq!(player_manager.get_pinned_session(), None);
+        assert_eq!(active_aumid(&player_manager).await.as_deref(), Some("A"));
+        drain_events(&mut player_manager);
+        session_manager.remove_session("A");
+        player_manager.update_sessions(None);
+        assert_eq!(
+            drain_events(&mut player_manager),
+            vec!["SessionRemoved(A)", "ActiveSessionChanged(B)"]
+        );
+    }
+
+    #[tokio::test]
+    async fn sessions_of_the_same_app_get_their_own_ids() {
+        let session_manager = MockSessionManager::new();
+        let a1 = session_manager.add_session("A");
+        let a2 = session_manager.add_session("A");
+        session_manager.add_session("B");
+        a2.set_playback_status(PlaybackStatus::Playing);
+        let mut player_manager = PlayerManager::from_session_manager(session_manager.clone());
+        drain_events(&mut player_manager);
+
+        player_manager.update_sessions(None);
+        assert_eq!(sorted_keys(&player_manager), vec!["A", "A#2", "B"]);
+        assert_eq!(
+            drain_events(&mut player_manager),
+            vec![
+                "SessionAdded(A)",
+                "SessionAdded(A#2)",
+                "SessionAdded(B)",
+                "ActiveSessionChanged(A#2)"
+            ]
+        );
+        assert_eq!(player_manager.get_session_ids_for("A"), vec!["A", "A#2"]);
+        assert_eq!(
+            player_manager.get_active_session_id().as_deref(),
+            Some("A#2")
+        );
+        let players = player_manager.get_sessions_for("A");
+        assert_eq!(players.len(), 2);
+        assert!(std::sync::Arc::ptr_eq(
+            &players[1],
+            &player_manager.get_session(&String::from("A#2")).unwrap()
+        ));
+
+        session_manager.remove(&a1);
+        player_manager.update_sessions(None);
+        assert_eq!(drain_events(&mut player_manager), vec!["SessionRemoved(A)"]);
+        assert_eq!(player_manager.get_session_ids_for("A"), vec!["A#2"]);
+
+        // the id is free again
+        session_manager.add_session("A");
+        player_manager.update_sessions(None);
+        assert_eq!(player_manager.get_session_ids_for("A"), vec!["A#2", "A"]);
+
+        // pinning an app stays on the session of it we are on
+        player_manager.pin_session("A");
+        assert_eq!(
+            player_manager.get_active_session_id().as_deref(),
+            Some("A#2")
+        );
+        session_manager.remove(&a2);
+        player_manager.update_sessions(None);
+        assert_eq!(player_manager.get_active_session_id().as_deref(), Some("A"));
+    }
+
+    // paused, so that sleeping only lets the players' tasks catch up
+    #[tokio::test(start_paused = true)]
+    async fn sessions_are_listed_by_recency() {
+        let session_manager = MockSessionManager::new();
+        let a = session_manager.add_session("A");
+        let b = session_manager.add_session("B");
+        b.set_playback_status(PlaybackStatus::Playing);
+        let mut player_manager = PlayerManager::from_session_manager(session_manager.clone());
+        player_manager.update_sessions(None);
+        session_manager.add_session("C");
+        player_manager.update_sessions(None);
+        assert_eq!(player_manager.get_sessions_keys(), vec!["A", "B", "C"]);
+
+        let ids = |player_manager: &PlayerManager<MockSessionManager>| {
+            player_manager
+                .get_sessions_by_recency()
+                .into_iter()
+                .map(|x| x.id)
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(ids(&player_manager), vec!["B", "C", "A"]);
+
+        a.set_playback_status(PlaybackStatus::Playing);
+        a.fire_playback_info_changed();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        b.set_playback_status(PlaybackStatus::Paused);
+        b.fire_playback_info_changed();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(ids(&player_manager), vec!["A", "B", "C"]);
+
+        let activities = player_manager.get_sessions_by_recency();
+        let (a, b, c) = (&activities[0], &activities[1], &activities[2]);
+        assert!(a.last_playing.unwrap() > b.last_playing.unwrap());
+        assert!(b.last_playing.unwrap() > b.first_seen);
+        assert!(b.last_event.is_some());
+        assert!(c.last_playing.is_none() && c.last_event.is_none());
+        assert!(c.first_seen >= a.first_seen);
+    }
+
+    #[tokio::test]
+    async fn idle_sessions_expire_until_they_play() {
+        let session_manager = MockSessionManager::new();
+        let a = session_manager.add_session("A");
+        let b = session_manager.add_session("B");
+        b.set_playback_status(PlaybackStatus::Playing);
+        let options = PlayerManagerOptions {
+            idle_timeout: Some(Duration::from_millis(100)),
+            ..Default::default()
+        };
+        let mut player_manager =
+            PlayerManager::from_session_manager_with_options(session_manager.clone(), options);
+        player_manager.update_sessions(None);
+        drain_events(&mut player_manager);
+
+        assert_eq!(
+            events_until(&mut player_manager, "SessionExpired(A)").await,
+            vec!["SessionExpired(A)"]
+        );
+        assert_eq!(player_manager.get_sessions_keys(), vec!["B"]);
+        assert!(player_manager.get_session(&String::from("A")).is_some());
+
+        b.set_playback_status(PlaybackStatus::Paused);
+        b.fire_playback_info_changed();
+        assert_eq!(
+            events_until(&mut player_manager, "ActiveSessionChanged()").await,
+            vec!["SessionExpired(B)", "ActiveSessionChanged()"]
+        );
+        assert!(player_manager.get_sessions_by_recency().is_empty());
+
+        // updating the sessions keeps them hidden
+        player_manager.update_sessions(None);
+        assert!(player_manager.get_active_session().is_none());
+
+        a.set_playback_status(PlaybackStatus::Playing);
+        a.fire_playback_info_changed();
+        assert_eq!(
+            events_until(&mut player_manager, "ActiveSessionChanged(A)").await,
+            vec!["SessionRevived(A)", "ActiveSessionChanged(A)"]
+        );
+        assert_eq!(player_manager.get_sessions_keys(), vec!["A"]);
+    }
+
+    #[tokio::test]
+    async fn all_statuses_share_one_timestamp() {
+        let session_manager = MockSessionManager::new();
+        let start = Utc::now() - chrono::Duration::seconds(10);
+        for (aumid, status) in [
+            ("A", PlaybackStatus::Playing),
+            ("B", PlaybackStatus::Paused),
+        ] {
+            let session = session_manager.add_session(aumid);
+            session.set_playback_status(status);
+            session.set_timeline_properties(Some(TimelineProperties {
+                start_time: Duration::ZERO,
+                end_time: Duration::from_secs(100),
+                position: Duration::from_secs(20),
+                last_updated_time: start,
+            }));
+            session.set_media_properties(Some(MediaProperties {
+                title: String::from("Title"),
+                artist: String::from("Artist"),
+                album_title: None,
+                album_artist: None,
+            }));
+            session.set_thumbnail(Some(ArtData {
+                data: vec![1, 2, 3],
+                mimetype: String::from("image/png"),
+            }));
+        }
+        let mut player_manager = PlayerManager::from_session_manager(session_manager.clone());
+        player_manager.update_sessions(None);
+
+        let statuses = player_manager.get_all_statuses(false);
+        let mut keys: Vec<&String> = statuses.keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["A", "B"]);
+
+        let a = statuses["A"].elapsed.as_ref().unwrap();
+        let b = statuses["B"].elapsed.as_ref().unwrap();
+        assert_eq!(a.when, b.when);
+        assert!(a.how_much >= 30.0 && a.how_much < 31.0);
+        assert_eq!(b.how_much, 20.0);
+        assert!(statuses["A"].metadata.as_ref().unwrap().art_data.is_none());
+
+        let statuses = player_manager.get_all_statuses(true);
+        assert!(statuses["B"].metadata.as_ref().unwrap().art_data.is_some());
+    }
+}
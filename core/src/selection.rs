@@ -0,0 +1,291 @@
+//! How a [`PlayerManager`](crate::PlayerManager) picks its active session.
+//!
+//! Every time the manager reconciles its sessions, and every time the
+//! playback of one of them changes, it asks its [`SelectionPolicy`] which of
+//! the tracked sessions should be the active one. [`DefaultPolicy`] is used
+//! unless told otherwise with
+//! [`PlayerManager::set_selection_policy`](crate::PlayerManager::set_selection_policy).
+
+use std::collections::{HashMap, HashSet};
+
+use crate::backend::PlaybackStatus;
+
+/// A tracked session a policy can pick.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// Id of the session, see [`crate::playermanager`].
+    pub id: String,
+    /// App User Model ID of the session.
+    pub aumid: String,
+    /// Its playback status, if it reports one.
+    pub playback_status: Option<PlaybackStatus>,
+}
+
+impl Candidate {
+    fn is_playing(&self) -> bool {
+        self.playback_status == Some(PlaybackStatus::Playing)
+    }
+}
+
+/// What a policy knows when asked to pick.
+#[derive(Debug, Clone)]
+pub struct SelectionContext<'a> {
+    /// Id of the active session so far, if any.
+    pub current: Option<&'a str>,
+    /// Id of the session the backend considers current, if tracked.
+    pub system: Option<&'a str>,
+    /// The tracked sessions, in the order the backend lists them.
+    pub candidates: &'a [Candidate],
+}
+
+impl SelectionContext<'_> {
+    fn get(&self, id: Option<&str>) -> Option<&Candidate> {
+        let id = id?;
+        self.candidates.iter().find(|x| x.id == id)
+    }
+
+    fn first_playing(&self) -> Option<&Candidate> {
+        self.candidates.iter().find(|x| x.is_playing())
+    }
+
+    // sessions not reporting any status are only picked on purpose
+    fn first_with_status(&self) -> Option<&Candidate> {
+        self.candidates.iter().find(|x| x.playback_status.is_some())
+    }
+}
+
+/// Picks the active session among the tracked ones.
+pub trait SelectionPolicy: Send {
+    /// The id of the session to make active, `None` for no active session.
+    /// Anything not among [`SelectionContext::candidates`] counts as `None`.
+    fn select(&mut self, context: &SelectionContext) -> Option<String>;
+}
+
+/// The first of: the active session so far, the system session, a playing
+/// session, any other session.
+#[derive(Debug, Clone, Default)]
+pub struct DefaultPolicy;
+
+impl SelectionPolicy for DefaultPolicy {
+    fn select(&mut self, context: &SelectionContext) -> Option<String> {
+        context
+            .get(context.current)
+            .or_else(|| context.get(context.system))
+            .or_else(|| context.first_playing())
+            .or_else(|| context.first_with_status())
+            .map(|x| x.id.clone())
+    }
+}
+
+/// Like [`DefaultPolicy`], without regard for the system session.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreSystemSession;
+
+impl SelectionPolicy for IgnoreSystemSession {
+    fn select(&mut self, context: &SelectionContext) -> Option<String> {
+        context
+            .get(context.current)
+            .or_else(|| context.first_playing())
+            .or_else(|| context.first_with_status())
+            .map(|x| x.id.clone())
+    }
+}
+
+/// The first tracked session in a fixed list of AUMIDs, preferring the
+/// active one among sessions of the same app; [`DefaultPolicy`] when none of
+/// them is around.
+#[derive(Debug, Clone, Default)]
+pub struct PriorityList {
+    /// The AUMIDs, most wanted first.
+    pub aumids: Vec<String>,
+}
+
+impl SelectionPolicy for PriorityList {
+    fn select(&mut self, context: &SelectionContext) -> Option<String> {
+        let current = context.get(context.current);
+        self.aumids
+            .iter()
+            .find_map(|aumid| {
+                current
+                    .filter(|x| &x.aumid == aumid)
+                    .or_else(|| context.candidates.iter().find(|x| &x.aumid == aumid))
+            })
+            .map(|x| x.id.clone())
+            .or_else(|| DefaultPolicy.select(context))
+    }
+}
+
+/// Keeps the active session until it stops or goes away, then moves to a
+/// playing session, if any.
+#[derive(Debug, Clone, Default)]
+pub struct StickyUntilStopped;
+
+impl SelectionPolicy for StickyUntilStopped {
+    fn select(&mut self, context: &SelectionContext) -> Option<String> {
+        context
+            .get(context.current)
+            .filter(|x| {
+                !matches!(
+                    x.playback_status,
+                    None | Some(PlaybackStatus::Stopped | PlaybackStatus::Closed)
+                )
+            })
+            .or_else(|| context.first_playing())
+            .or_else(|| context.first_with_status())
+            .map(|x| x.id.clone())
+    }
+}
+
+/// The session that most recently started playing; while nothing plays, the
+/// active session stays as it is.
+#[derive(Debug, Clone, Default)]
+pub struct MostRecentlyPlaying {
+    playing: HashSet<String>,
+    started: HashMap<String, u64>,
+    counter: u64,
+}
+
+impl SelectionPolicy for MostRecentlyPlaying {
+    fn select(&mut self, context: &SelectionContext) -> Option<String> {
+        // we only see what plays when asked, so that is when it started
+        let playing: HashSet<String> = context
+            .candidates
+            .iter()
+            .filter(|x| x.is_playing())
+            .map(|x| x.id.clone())
+            .collect();
+        for id in playing.difference(&self.playing) {
+            self.counter += 1;
+            self.started.insert(id.clone(), self.counter);
+        }
+        self.started
+            .retain(|id, _| context.candidates.iter().any(|x| &x.id == id));
+        self.playing = playing;
+
+        self.playing
+            .iter()
+            .max_by_key(|id| self.started.get(*id))
+            .and_then(|id| context.get(Some(id)))
+            .or_else(|| context.get(context.current))
+            .or_else(|| context.first_with_status())
+            .map(|x| x.id.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Candidate, DefaultPolicy, IgnoreSystemSession, MostRecentlyPlaying, PriorityList,
+        SelectionContext, SelectionPolicy, StickyUntilStopped,
+    };
+    use crate::backend::PlaybackStatus;
+
+    fn candidates(statuses: &[(&str, PlaybackStatus)]) -> Vec<Candidate> {
+        statuses
+            .iter()
+            .map(|(aumid, status)| Candidate {
+                id: aumid.to_string(),
+                aumid: aumid.to_string(),
+                playback_status: Some(*status),
+            })
+            .collect()
+    }
+
+    fn select(
+        policy: &mut impl SelectionPolicy,
+        current: Option<&str>,
+        system: Option<&str>,
+        candidates: &[Candidate],
+    ) -> Option<String> {
+        policy.select(&SelectionContext {
+            current,
+            system,
+            candidates,
+        })
+    }
+
+    #[test]
+    fn built_in_policies() {
+        let sessions = candidates(&[
+            ("A", PlaybackStatus::Stopped),
+            ("B", PlaybackStatus::Playing),
+            ("C", PlaybackStatus::Paused),
+        ]);
+
+        assert_eq!(
+            select(&mut DefaultPolicy, Some("A"), Some("C"), &sessions).as_deref(),
+            Some("A")
+        );
+        assert_eq!(
+            select(&mut DefaultPolicy, None, Some("C"), &sessions).as_deref(),
+            Some("C")
+        );
+        assert_eq!(
+            select(&mut DefaultPolicy, Some("gone"), None, &sessions).as_deref(),
+            Some("B")
+        );
+
+        assert_eq!(
+            select(&mut IgnoreSystemSession, None, Some("C"), &sessions).as_deref(),
+            Some("B")
+        );
+
+        let mut priority = PriorityList {
+            aumids: vec![String::from("gone"), String::from("C"), String::from("A")],
+        };
+        assert_eq!(
+            select(&mut priority, Some("B"), None, &sessions).as_deref(),
+            Some("C")
+        );
+
+        assert_eq!(
+            select(&mut StickyUntilStopped, Some("C"), None, &sessions).as_deref(),
+            Some("C")
+        );
+        assert_eq!(
+            select(&mut StickyUntilStopped, Some("A"), None, &sessions).as_deref(),
+            Some("B")
+        );
+
+        assert_eq!(select(&mut DefaultPolicy, None, None, &[]), None);
+    }
+
+    #[test]
+    fn most_recently_playing_follows_playback_starts() {
+        let mut policy = MostRecentlyPlaying::default();
+
+        let sessions = candidates(&[
+            ("A", PlaybackStatus::Playing),
+            ("B", PlaybackStatus::Paused),
+        ]);
+        assert_eq!(
+            select(&mut policy, None, None, &sessions).as_deref(),
+            Some("A")
+        );
+
+        let sessions = candidates(&[
+            ("A", PlaybackStatus::Playing),
+            ("B", PlaybackStatus::Playing),
+        ]);
+        assert_eq!(
+            select(&mut policy, Some("A"), None, &sessions).as_deref(),
+            Some("B")
+        );
+
+        // nothing playing: stay where we are
+        let sessions = candidates(&[("A", PlaybackStatus::Paused), ("B", PlaybackStatus::Paused)]);
+        assert_eq!(
+            select(&mut policy, Some("B"), None, &sessions).as_deref(),
+            Some("B")
+        );
+
+        let sessions = candidates(&[
+            ("A", PlaybackStatus::Playing),
+            ("B", PlaybackStatus::Paused),
+        ]);
+        assert_eq!(
+            select(&mut policy, Some("B"), None, &sessions).as_deref(),
+            Some("A")
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Backend independent snapshots of a session's state.
+
+use chrono::{DateTime, Utc};
+
+/// Cover art, as raw image bytes.
+#[derive(Debug, Clone)]
+pub struct ArtData {
+    /// The encoded image.
+    pub data: Vec<u8>,
+    /// The MIME type of `data`, e.g. `image/png`.
+    pub mimetype: String,
+}
+
+/// What is currently playing.
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    /// Album title, if the session reports one.
+    pub album: Option<String>,
+    /// Album artist, as reported by the session and normalized.
+    pub album_artist: Option<String>,
+    /// Album artists, one per entry.
+    pub album_artists: Option<Vec<String>>,
+    /// Artist, as reported by the session and normalized.
+    pub artist: String,
+    /// Artists, one per entry.
+    pub artists: Vec<String>,
+    /// Cover art, if any.
+    pub art_data: Option<ArtData>,
+    /// A stable identifier for the track, derived from artists, album and title.
+    pub id: Option<String>,
+    /// Track length in seconds.
+    pub length: f64,
+    /// Track title, normalized.
+    pub title: String,
+    /// The values as the session reported them, if
+    /// [normalization](crate::metadata::NormalizationRules) changed any.
+    pub raw: Option<RawMetadata>,
+}
+
+/// The fields of [`Metadata`] as the session reported them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawMetadata {
+    /// Album artist.
+    pub album_artist: Option<String>,
+    /// Artist.
+    pub artist: String,
+    /// Track title.
+    pub title: String,
+}
+
+/// Which controls a session currently accepts.
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    /// Whether any of the controls below is available.
+    pub can_control: bool,
+    /// Whether play, pause and toggling between them are available.
+    pub can_play_pause: bool,
+    /// Whether skipping to the next track is available.
+    pub can_go_next: bool,
+    /// Whether skipping to the previous track is available.
+    pub can_go_previous: bool,
+    /// Whether changing the playback position is available.
+    pub can_seek: bool,
+}
+
+/// A playback position and the moment it refers to.
+#[derive(Debug, Clone)]
+pub struct Position {
+    /// Seconds from the start of the track.
+    pub how_much: f64,
+    /// When the session was at `how_much`.
+    pub when: DateTime<Utc>,
+}
+
+/// When a session of a [`PlayerManager`](crate::PlayerManager) was active.
+#[derive(Debug, Clone)]
+pub struct SessionActivity {
+    /// Id of the session, see [`crate::playermanager`].
+    pub id: String,
+    /// App User Model ID of the session.
+    pub aumid: String,
+    /// When the manager started tracking it.
+    pub first_seen: DateTime<Utc>,
+    /// When it was last known to be playing: now if it still is, when it
+    /// stopped if not, `None` if it never played while tracked.
+    pub last_playing: Option<DateTime<Utc>>,
+    /// When it last sent an event other than a position tick, if it did.
+    pub last_event: Option<DateTime<Utc>>,
+}
+
+/// Everything there is to know about a session at a given time.
+#[derive(Debug, Clone)]
+pub struct Status {
+    /// What is playing, if anything.
+    pub metadata: Option<Metadata>,
+    /// Which controls are available.
+    pub capabilities: Capabilities,
+    /// Playback status: `Playing`, `Paused`, `Stopped`, ... or `Unknown`.
+    pub status: String,
+    /// Repeat mode: `None`, `Track` or `List`.
+    pub is_loop: String,
+    /// Whether shuffle is on.
+    pub shuffle: bool,
+    /// Always -1, sessions do not expose their volume.
+    pub volume: f64, // tanto sta a -1 lmao
+    /// Playback position, as of the last time the session reported it.
+    pub elapsed: Option<Position>,
+    /// App User Model ID of the session.
+    pub app: Option<String>, // App User Model ID
+}
+
+/// The value of a single field of [`Status`], as reported in a [`FieldChange`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    /// A flag, such as [`Status::shuffle`].
+    Bool(bool),
+    /// A number, such as [`Metadata::length`].
+    Number(f64),
+    /// A string, such as [`Status::status`].
+    Text(String),
+    /// A list, such as [`Metadata::artists`].
+    List(Vec<String>),
+}
+
+/// A field of [`Status`] that changed, with what it was and what it is now.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    /// The path of the field, e.g. `shuffle`, `capabilities.can_seek` or
+    /// `metadata.title`.
+    pub field: &'static str,
+    /// The value before; `None` if there was none, e.g. no metadata at all.
+    pub old: Option<FieldValue>,
+    /// The value now; `None` if there is none.
+    pub new: Option<FieldValue>,
+}
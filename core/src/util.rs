@@ -0,0 +1,353 @@
+//! Helpers to turn backend snapshots into [`crate::types`] values.
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    backend::{AutoRepeatMode, MediaSession, PlaybackInfo, PlaybackStatus, TimelineProperties},
+    types::{Capabilities, FieldChange, FieldValue, Metadata, Position, Status},
+};
+
+/// The string form of a repeat mode, as used in [`Status::is_loop`](crate::Status::is_loop).
+pub fn autorepeat_to_string(autorepeat: AutoRepeatMode) -> String {
+    format!("{:?}", autorepeat)
+}
+
+/// The string form of a playback status, as used in [`Status::status`](crate::Status::status).
+pub fn playback_status_to_string(status: PlaybackStatus) -> String {
+    format!("{:?}", status)
+}
+
+/// The playback status of a session, in the same form as [`Status::status`](crate::Status::status).
+pub fn get_playback_status(playback_info: Option<&PlaybackInfo>) -> String {
+    if let Some(playback_info) = playback_info {
+        if let Some(status) = playback_info.playback_status {
+            return playback_status_to_string(status);
+        }
+    }
+    String::from("Unknown")
+}
+
+/// The repeat mode of a session, in the same form as [`Status::is_loop`](crate::Status::is_loop).
+pub fn get_repeat_mode(playback_info: Option<&PlaybackInfo>) -> String {
+    if let Some(playback_info) = playback_info {
+        if let Some(mode) = playback_info.auto_repeat_mode {
+            return autorepeat_to_string(mode);
+        }
+    }
+    String::from("None")
+}
+
+/// Whether shuffle is on; unknown counts as off.
+pub fn get_shuffle(playback_info: Option<&PlaybackInfo>) -> bool {
+    playback_info
+        .and_then(|x| x.is_shuffle_active)
+        .unwrap_or(false)
+}
+
+/// Computes the position within the track from the timeline of a session.
+///
+/// With `account_for_time_skew` the time elapsed since the session last
+/// updated its timeline is added when playing, so that the result is the
+/// position as of now.
+pub fn compute_position(
+    timeline_properties: Option<&TimelineProperties>,
+    playback_info: Option<&PlaybackInfo>,
+    account_for_time_skew: bool,
+) -> Option<Position> {
+    if let Some(timeline_properties) = timeline_properties {
+        let playback_status = playback_info
+            .and_then(|x| x.playback_status)
+            .unwrap_or(PlaybackStatus::Stopped);
+
+        let mut when: DateTime<Utc> = timeline_properties.last_updated_time;
+
+        let end_time: f64 = timeline_properties.end_time.as_secs_f64();
+
+        let mut position: f64 = timeline_properties.position.as_secs_f64()
+            - timeline_properties.start_time.as_secs_f64();
+
+        if end_time == 0f64 {
+            return None;
+        }
+
+        if account_for_time_skew && playback_status == PlaybackStatus::Playing {
+            let now = Utc::now();
+            let time_from_last_update = now.timestamp_millis() - when.timestamp_millis();
+            position += time_from_last_update as f64 / 1000f64;
+            when = now;
+        }
+
+        return Some(Position {
+            how_much: position,
+            when,
+        });
+    }
+    None
+}
+
+/// Reads which controls a session currently accepts.
+pub fn get_session_capabilities<S: MediaSession>(session: &S) -> Capabilities {
+    if let Some(playback_info) = session.playback_info() {
+        if let Some(controls) = playback_info.controls {
+            let can_play_pause = controls.is_play_enabled || controls.is_pause_enabled;
+            let can_go_next = controls.is_next_enabled;
+            let can_go_previous = controls.is_previous_enabled;
+            let can_seek = {
+                let is_pp_enabled = controls.is_playback_position_enabled;
+                let is_endtime = 'rt: {
+                    if let Some(p) = session.timeline_properties() {
+                        break 'rt !p.end_time.is_zero();
+                    }
+                    false
+                };
+                is_pp_enabled && is_endtime
+            };
+            let can_control = can_play_pause || can_go_next || can_go_previous || can_seek;
+
+            return Capabilities {
+                can_control,
+                can_play_pause,
+                can_go_next,
+                can_go_previous,
+                can_seek,
+            };
+        }
+    }
+    Capabilities {
+        can_control: false,
+        can_play_pause: false,
+        can_go_next: false,
+        can_go_previous: false,
+        can_seek: false,
+    }
+}
+
+/// The length of the track, in seconds, as told by its timeline.
+pub fn compute_length(timeline_properties: &TimelineProperties) -> f64 {
+    timeline_properties.end_time.as_secs_f64() - timeline_properties.start_time.as_secs_f64()
+}
+
+/// Reads what a session is playing, cover art included only if `with_art` is
+/// set.
+pub fn get_session_metadata<S: MediaSession>(session: &S, with_art: bool) -> Option<Metadata> {
+    if let Some(timeline_properties) = session.timeline_properties() {
+        if let Some(info) = session.media_properties() {
+            let title = info.title;
+
+            let album = info.album_title;
+
+            let album_artist = info.album_artist;
+
+            let album_artists = album_artist.clone().map(|artist| vec![artist]);
+
+            let artist = info.artist;
+
+            let artists = vec![artist.clone()];
+
+            // fetching the art can mean reading a file or worse
+            let art_data = if with_art { session.thumbnail() } else { None };
+
+            let id = 'rt: {
+                let id = format!(
+                    "{}{}{}{}",
+                    album_artist.clone().unwrap_or_default(),
+                    artist,
+                    album.clone().unwrap_or_default(),
+                    title
+                );
+                if !id.is_empty() {
+                    let md5 = md5::compute(id);
+                    break 'rt Some(format!("{:x}", md5).to_string());
+                }
+                None
+            };
+
+            let length = compute_length(&timeline_properties);
+
+            return Some(Metadata {
+                album,
+                album_artist,
+                album_artists,
+                artist,
+                artists,
+                art_data,
+                id,
+                length,
+                title,
+                raw: None,
+            });
+        }
+    }
+    None
+}
+
+/// Everything there is to know about a session belonging to `aumid`, cover
+/// art included only if `with_art` is set.
+pub fn get_session_status<S: MediaSession>(session: &S, aumid: &str, with_art: bool) -> Status {
+    let playback_info = session.playback_info();
+    let timeline_properties = session.timeline_properties();
+
+    Status {
+        metadata: get_session_metadata(session, with_art),
+        capabilities: get_session_capabilities(session),
+        status: get_playback_status(playback_info.as_ref()),
+        is_loop: get_repeat_mode(playback_info.as_ref()),
+        shuffle: get_shuffle(playback_info.as_ref()),
+        volume: -1f64,
+        elapsed: compute_position(timeline_properties.as_ref(), playback_info.as_ref(), false),
+        app: Some(aumid.to_string()),
+    }
+}
+
+/// Same as [`get_session_status`], with the position as of `now` rather than
+/// as of when the session last reported it, so that statuses taken together
+/// share the same timestamp.
+pub fn get_session_status_at<S: MediaSession>(
+    session: &S,
+    aumid: &str,
+    with_art: bool,
+    now: DateTime<Utc>,
+) -> Status {
+    let mut status = get_session_status(session, aumid, with_art);
+    let playing = status.status == playback_status_to_string(PlaybackStatus::Playing);
+    status.elapsed = status.elapsed.map(|position| Position {
+        how_much: if playing {
+            position.how_much + (now - position.when).num_milliseconds() as f64 / 1000f64
+        } else {
+            position.how_much
+        },
+        when: now,
+    });
+    status
+}
+
+fn status_fields(status: &Status) -> Vec<(&'static str, Option<FieldValue>)> {
+    let text = |x: &String| Some(FieldValue::Text(x.clone()));
+    let list = |x: &Vec<String>| Some(FieldValue::List(x.clone()));
+    let capabilities = &status.capabilities;
+    let metadata = status.metadata.as_ref();
+
+    vec![
+        ("status", text(&status.status)),
+        ("is_loop", text(&status.is_loop)),
+        ("shuffle", Some(FieldValue::Bool(status.shuffle))),
+        ("volume", Some(FieldValue::Number(status.volume))),
+        (
+            "capabilities.can_control",
+            Some(FieldValue::Bool(capabilities.can_control)),
+        ),
+        (
+            "capabilities.can_play_pause",
+            Some(FieldValue::Bool(capabilities.can_play_pause)),
+        ),
+        (
+            "capabilities.can_go_next",
+            Some(FieldValue::Bool(capabilities.can_go_next)),
+        ),
+        (
+            "capabilities.can_go_previous",
+            Some(FieldValue::Bool(capabilities.can_go_previous)),
+        ),
+        (
+            "capabilities.can_seek",
+            Some(FieldValue::Bool(capabilities.can_seek)),
+        ),
+        (
+            "metadata.album",
+            metadata.and_then(|x| x.album.as_ref()).and_then(text),
+        ),
+        (
+            "metadata.album_artist",
+            metadata
+                .and_then(|x| x.album_artist.as_ref())
+                .and_then(text),
+        ),
+        (
+            "metadata.album_artists",
+            metadata
+                .and_then(|x| x.album_artists.as_ref())
+                .and_then(list),
+        ),
+        ("metadata.artist", metadata.and_then(|x| text(&x.artist))),
+        ("metadata.artists", metadata.and_then(|x| list(&x.artists))),
+        (
+            "metadata.id",
+            metadata.and_then(|x| x.id.as_ref()).and_then(text),
+        ),
+        (
+            "metadata.length",
+            metadata.map(|x| FieldValue::Number(x.length)),
+        ),
+        ("metadata.title", metadata.and_then(|x| text(&x.title))),
+    ]
+}
+
+/// The fields that differ between two statuses, in declaration order.
+///
+/// The position is left out, it moves on its own while playing, and so is the
+/// cover art; [`Status::app`] never changes for a given session.
+pub fn diff_status(old: &Status, new: &Status) -> Vec<FieldChange> {
+    status_fields(old)
+        .into_iter()
+        .zip(status_fields(new))
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((field, old), (_, new))| FieldChange { field, old, new })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+
+    use super::compute_position;
+    use crate::backend::{PlaybackInfo, PlaybackStatus, TimelineProperties};
+
+    fn timeline(position: u64, end_time: u64) -> TimelineProperties {
+        TimelineProperties {
+            start_time: Duration::from_secs(2),
+            end_time: Duration::from_secs(end_time),
+            position: Duration::from_secs(position),
+            last_updated_time: Utc.timestamp_millis_opt(1_000_000).unwrap(),
+        }
+    }
+
+    fn playback_info(playback_status: PlaybackStatus) -> PlaybackInfo {
+        PlaybackInfo {
+            playback_status: Some(playback_status),
+            auto_repeat_mode: None,
+            is_shuffle_active: None,
+            controls: None,
+        }
+    }
+
+    #[test]
+    fn position_is_relative_to_start_time() {
+        let position = compute_position(Some(&timeline(30, 200)), None, false).unwrap();
+        assert_eq!(position.how_much, 28f64);
+        assert_eq!(position.when, Utc.timestamp_millis_opt(1_000_000).unwrap());
+    }
+
+    #[test]
+    fn no_position_without_timeline_or_end_time() {
+        assert!(compute_position(None, None, true).is_none());
+        assert!(compute_position(Some(&timeline(30, 0)), None, false).is_none());
+    }
+
+    #[test]
+    fn time_skew_only_applies_while_playing() {
+        let mut timeline = timeline(30, 200);
+        timeline.last_updated_time = Utc::now() - chrono::Duration::seconds(10);
+
+        let paused = playback_info(PlaybackStatus::Paused);
+        let position = compute_position(Some(&timeline), Some(&paused), true).unwrap();
+        assert_eq!(position.how_much, 28f64);
+        assert_eq!(position.when, timeline.last_updated_time);
+
+        let playing = playback_info(PlaybackStatus::Playing);
+        let position = compute_position(Some(&timeline), Some(&playing), true).unwrap();
+        assert!((position.how_much - 38f64).abs() < 1f64);
+        assert!(position.when > timeline.last_updated_time);
+    }
+}
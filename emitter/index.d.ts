@@ -1,12 +1,22 @@
 /// <reference types="node" />
-import { Player, PlayerManager } from "..";
+import { Player, PlayerManager, PlayerManagerOptions, SessionFilter } from "..";
 import { EventEmitter } from "events";
-export type { Status, Position, ArtData, Metadata, Capabilities } from "..";
+export type { Status, Position, ArtData, Metadata, RawMetadata, Capabilities, PlayerEvent, PlaybackInfoChangedEvent, MediaPropertiesChangedEvent, TimelinePropertiesChangedEvent, TrackChangedEvent, SeekedEvent, PositionTickEvent, FieldChange, StatusChangedEvent, ManagerEvent, PlayerManagerOptions, SessionFilter, SessionActivity, ArtistSplitting, Normalization, } from "..";
 export declare class WinPlayer extends EventEmitter {
     playerManager: PlayerManager;
     denylist: string[];
+    autoSync: boolean;
+    filter: SessionFilter;
     player: Player | null;
-    constructor(playerManager: PlayerManager, denylist?: string[]);
+    private managerSubscription;
+    private playerSubscription;
+    private playerEvents;
+    constructor(playerManager: PlayerManager, denylist?: string[], autoSync?: boolean, filter?: SessionFilter);
+    close(): void;
+    pinSession(aumid: string): void;
+    unpinSession(): void;
+    getAllStatuses(withArt?: boolean): Promise<Record<string, import("..").Status>>;
+    getSessionsByRecency(): import("..").SessionActivity[];
     getFriendlyName(): Promise<string | null>;
     getStatus(): Promise<import("..").Status | undefined>;
     play(): Promise<boolean | undefined>;
@@ -26,4 +36,4 @@ export declare class WinPlayer extends EventEmitter {
     setPosition(position: number): Promise<boolean | undefined>;
     getPosition(): Promise<import("..").Position>;
 }
-export default function init(): Promise<WinPlayer | undefined>;
+export default function init(options?: PlayerManagerOptions): Promise<WinPlayer | undefined>;
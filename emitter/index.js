@@ -6,56 +6,88 @@ const events_1 = require("events");
 class WinPlayer extends events_1.EventEmitter {
     playerManager;
     denylist;
+    autoSync;
+    filter;
     player = null;
-    constructor(playerManager, denylist) {
+    managerSubscription = null;
+    playerSubscription = null;
+    playerEvents = Promise.resolve();
+    constructor(playerManager, denylist, autoSync, filter) {
         super();
         this.playerManager = playerManager;
         this.denylist = denylist ?? [];
-        const _managerEvents = async () => {
-            for (;;) {
-                if (!this.playerManager)
+        this.autoSync = autoSync ?? false;
+        this.filter = filter ?? {};
+        const _managerEvent = (evt) => {
+            switch (evt.type) {
+                case "ActiveSessionChanged":
+                    this.playerSubscription?.unsubscribe();
+                    this.playerSubscription = null;
+                    this.player = this.playerManager.getActiveSession();
+                    const player = this.player;
+                    if (player)
+                        this.playerSubscription = player.subscribe((evt) => _playerEvent(player, evt));
+                    this.emit(evt.type, evt.aumid, evt.id);
                     break;
-                const evt = await this.playerManager.pollNextEvent();
-                switch (evt) {
-                    case "ActiveSessionChanged":
-                        this.player = null;
-                        const player = this.playerManager.getActiveSession();
-                        if (player) {
-                            this.player = player;
-                            _playerEvents();
-                        }
-                        this.emit(evt, await this.player?.getAumid());
-                        break;
-                    case "SystemSessionChanged":
+                case "SystemSessionChanged":
+                    if (!this.autoSync)
                         this.playerManager.updateSystemSession();
-                        this.emit(evt, await this.playerManager.getSystemSession()?.getAumid());
-                        break;
-                    case "SessionsChanged":
-                        this.playerManager.updateSessions(this.denylist);
-                        this.emit(evt, this.playerManager.getSessionsKeys());
-                        break;
-                }
+                    this.emit(evt.type, evt.aumid);
+                    break;
+                case "SessionsChanged":
+                    if (!this.autoSync)
+                        this.playerManager.updateSessionsWith({
+                            ...this.filter,
+                            deny: [...(this.filter.deny ?? []), ...this.denylist],
+                        });
+                    this.emit(evt.type, this.playerManager.getSessionsKeys());
+                    break;
+                case "SessionAdded":
+                case "SessionRemoved":
+                case "SessionExpired":
+                case "SessionRevived":
+                    this.emit(evt.type, evt.aumid, evt.id);
+                    break;
             }
         };
-        const _playerEvents = async () => {
-            for (;;) {
-                if (!this.player)
+        const _emitPlayerEvent = async (player, evt) => {
+            switch (evt.type) {
+                case "PlaybackInfoChanged":
+                case "MediaPropertiesChanged":
+                    this.emit(evt.type, await player.getStatus(), evt);
+                    break;
+                case "TimelinePropertiesChanged":
+                    this.emit(evt.type, await player.getPosition(false), evt);
+                    break;
+                default:
+                    this.emit(evt.type, evt);
                     break;
-                const evt = await this.player.pollNextEvent();
-                switch (evt) {
-                    case "PlaybackInfoChanged":
-                        this.emit(evt, await this.player.getStatus());
-                        break;
-                    case "TimelinePropertiesChanged":
-                        this.emit(evt, await this.player.getPosition(false));
-                        break;
-                    case "MediaPropertiesChanged":
-                        this.emit(evt, await this.player.getStatus());
-                        break;
-                }
             }
         };
-        _managerEvents();
+        const _playerEvent = (player, evt) => {
+            this.playerEvents = this.playerEvents.then(() => _emitPlayerEvent(player, evt));
+        };
+        this.managerSubscription = this.playerManager.subscribe(_managerEvent);
+        _managerEvent({ type: "SessionsChanged" });
+    }
+    close() {
+        this.playerSubscription?.unsubscribe();
+        this.playerSubscription = null;
+        this.managerSubscription?.unsubscribe();
+        this.managerSubscription = null;
+        this.player = null;
+    }
+    pinSession(aumid) {
+        this.playerManager.pinSession(aumid);
+    }
+    unpinSession() {
+        this.playerManager.unpinSession();
+    }
+    async getAllStatuses(withArt) {
+        return await this.playerManager.getAllStatuses(withArt);
+    }
+    getSessionsByRecency() {
+        return this.playerManager.getSessionsByRecency();
     }
     async getFriendlyName() {
         if (this.player)
@@ -132,10 +164,10 @@ class WinPlayer extends events_1.EventEmitter {
     }
 }
 exports.WinPlayer = WinPlayer;
-async function init() {
-    const playerManager = await (0, __1.getPlayerManager)();
+async function init(options) {
+    const playerManager = await (0, __1.getPlayerManager)(options);
     if (playerManager)
-        return new WinPlayer(playerManager);
+        return new WinPlayer(playerManager, options?.denylist, options?.autoSync, options?.filter);
     return undefined;
 }
 exports.default = init;
@@ -1,65 +1,142 @@
-import { Player, PlayerManager, getFriendlyNameFor, getPlayerManager } from "..";
+import {
+	ManagerEvent,
+	Player,
+	PlayerEvent,
+	PlayerManager,
+	PlayerManagerOptions,
+	SessionFilter,
+	Subscription,
+	getFriendlyNameFor,
+	getPlayerManager,
+} from "..";
 import { EventEmitter } from "events";
 
-export type { Status, Position, ArtData, Metadata, Capabilities } from "..";
+export type {
+	Status,
+	Position,
+	ArtData,
+	Metadata,
+	RawMetadata,
+	Capabilities,
+	PlayerEvent,
+	PlaybackInfoChangedEvent,
+	MediaPropertiesChangedEvent,
+	TimelinePropertiesChangedEvent,
+	TrackChangedEvent,
+	SeekedEvent,
+	PositionTickEvent,
+	FieldChange,
+	StatusChangedEvent,
+	ManagerEvent,
+	PlayerManagerOptions,
+	SessionFilter,
+	SessionActivity,
+	ArtistSplitting,
+	Normalization,
+} from "..";
 
 export class WinPlayer extends EventEmitter {
 	playerManager: PlayerManager;
 	denylist: string[];
+	autoSync: boolean;
+	filter: SessionFilter;
 
 	player: Player | null = null;
 
-	constructor(playerManager: PlayerManager, denylist?: string[]) {
+	private managerSubscription: Subscription | null = null;
+	private playerSubscription: Subscription | null = null;
+	private playerEvents: Promise<void> = Promise.resolve();
+
+	constructor(playerManager: PlayerManager, denylist?: string[], autoSync?: boolean, filter?: SessionFilter) {
 		super();
 		this.playerManager = playerManager;
 		this.denylist = denylist ?? [];
+		// the manager keeps itself up to date, with the filter it was made with
+		this.autoSync = autoSync ?? false;
+		this.filter = filter ?? {};
 
 		// closures to not expose them into the class
-		const _managerEvents = async () => {
-			for(;;) {
-				if (!this.playerManager) break;
-				const evt = await this.playerManager.pollNextEvent();
-				switch (evt) {
-					case "ActiveSessionChanged":
-						this.player = null;
-						const player = this.playerManager.getActiveSession();
-						if (player) {
-							this.player = player;
-							_playerEvents();
-						}
-						this.emit(evt, await this.player?.getAumid());
-						break;
-					case "SystemSessionChanged":
+		const _managerEvent = (evt: ManagerEvent) => {
+			switch (evt.type) {
+				case "ActiveSessionChanged":
+					this.playerSubscription?.unsubscribe();
+					this.playerSubscription = null;
+					this.player = this.playerManager.getActiveSession();
+					const player = this.player;
+					if (player)
+						this.playerSubscription = player.subscribe((evt) => _playerEvent(player, evt));
+					this.emit(evt.type, evt.aumid, evt.id);
+					break;
+				case "SystemSessionChanged":
+					if (!this.autoSync)
 						this.playerManager.updateSystemSession();
-						this.emit(evt, await this.playerManager.getSystemSession()?.getAumid());
-						break;
-					case "SessionsChanged":
-						this.playerManager.updateSessions(this.denylist);
-						this.emit(evt, this.playerManager.getSessionsKeys());
-						break;
-				}
+					this.emit(evt.type, evt.aumid);
+					break;
+				case "SessionsChanged":
+					if (!this.autoSync)
+						this.playerManager.updateSessionsWith({
+							...this.filter,
+							deny: [...(this.filter.deny ?? []), ...this.denylist],
+						});
+					this.emit(evt.type, this.playerManager.getSessionsKeys());
+					break;
+				case "SessionAdded":
+				case "SessionRemoved":
+				case "SessionExpired":
+				case "SessionRevived":
+					this.emit(evt.type, evt.aumid, evt.id);
+					break;
 			}
 		}
 
-		const _playerEvents = async () =>  {
-			for (;;) {
-				if (!this.player) break;
-				const evt = await this.player.pollNextEvent();
-				switch (evt) {
-					case "PlaybackInfoChanged":
-						this.emit(evt, await this.player.getStatus());
-						break;
-					case "TimelinePropertiesChanged":
-						this.emit(evt, await this.player.getPosition(false));
-						break;
-					case "MediaPropertiesChanged":
-						this.emit(evt, await this.player.getStatus());
-						break;
-				}
+		// the first three still hand out what they always did, the event comes after
+		const _emitPlayerEvent = async (player: Player, evt: PlayerEvent) => {
+			switch (evt.type) {
+				case "PlaybackInfoChanged":
+				case "MediaPropertiesChanged":
+					this.emit(evt.type, await player.getStatus(), evt);
+					break;
+				case "TimelinePropertiesChanged":
+					this.emit(evt.type, await player.getPosition(false), evt);
+					break;
+				default:
+					this.emit(evt.type, evt);
+					break;
 			}
 		}
 
-		_managerEvents();
+		// chained, so that events keep their order while the payloads are read
+		const _playerEvent = (player: Player, evt: PlayerEvent) => {
+			this.playerEvents = this.playerEvents.then(() => _emitPlayerEvent(player, evt));
+		}
+
+		this.managerSubscription = this.playerManager.subscribe(_managerEvent);
+		// the manager announces the sessions it starts with before we get to subscribe
+		_managerEvent({ type: "SessionsChanged" });
+	}
+
+	close() {
+		this.playerSubscription?.unsubscribe();
+		this.playerSubscription = null;
+		this.managerSubscription?.unsubscribe();
+		this.managerSubscription = null;
+		this.player = null;
+	}
+
+	pinSession(aumid: string) {
+		this.playerManager.pinSession(aumid);
+	}
+
+	unpinSession() {
+		this.playerManager.unpinSession();
+	}
+
+	async getAllStatuses(withArt?: boolean) {
+		return await this.playerManager.getAllStatuses(withArt);
+	}
+
+	getSessionsByRecency() {
+		return this.playerManager.getSessionsByRecency();
 	}
 
 	async getFriendlyName() {
@@ -155,9 +232,9 @@ export class WinPlayer extends EventEmitter {
 	}
 }
 
-export default async function init(): Promise<WinPlayer | undefined> {
-	const playerManager = await getPlayerManager();
+export default async function init(options?: PlayerManagerOptions): Promise<WinPlayer | undefined> {
+	const playerManager = await getPlayerManager(options);
 	if (playerManager)
-		return new WinPlayer(playerManager);
+		return new WinPlayer(playerManager, options?.denylist, options?.autoSync, options?.filter);
 	return undefined
 }
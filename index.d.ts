@@ -23,6 +23,12 @@ export interface Metadata {
   id?: string
   length: number
   title: string
+  raw?: RawMetadata
+}
+export interface RawMetadata {
+  albumArtist?: string
+  artist: string
+  title: string
 }
 export interface Capabilities {
   canControl: boolean
@@ -35,6 +41,13 @@ export interface Position {
   howMuch: number
   when: Date
 }
+export interface SessionActivity {
+  id: string
+  aumid: string
+  firstSeen: Date
+  lastPlaying?: Date
+  lastEvent?: Date
+}
 export interface Status {
   metadata?: Metadata
   capabilities: Capabilities
@@ -45,12 +58,117 @@ export interface Status {
   elapsed?: Position
   app?: string
 }
-export function getPlayerManager(): Promise<PlayerManager | null>
+export interface PlaybackInfoChangedEvent {
+  type: 'PlaybackInfoChanged'
+  status: string
+  isLoop: string
+  shuffle: boolean
+  capabilities: Capabilities
+}
+export interface MediaPropertiesChangedEvent {
+  type: 'MediaPropertiesChanged'
+  metadata?: Metadata
+}
+export interface TimelinePropertiesChangedEvent {
+  type: 'TimelinePropertiesChanged'
+  position?: Position
+}
+export interface TrackChangedEvent {
+  type: 'TrackChanged'
+  previous?: Metadata
+  current: Metadata
+}
+export interface SeekedEvent {
+  type: 'Seeked'
+  from: number
+  to: number
+}
+export interface PositionTickEvent {
+  type: 'PositionTick'
+  position: Position
+}
+export interface FieldChange {
+  field: string
+  old?: boolean | number | string | Array<string>
+  new?: boolean | number | string | Array<string>
+}
+export interface StatusChangedEvent {
+  type: 'StatusChanged'
+  changes: Array<FieldChange>
+}
+export interface SessionsChangedEvent {
+  type: 'SessionsChanged'
+}
+export interface SessionAddedEvent {
+  type: 'SessionAdded'
+  id: string
+  aumid: string
+}
+export interface SessionRemovedEvent {
+  type: 'SessionRemoved'
+  id: string
+  aumid: string
+}
+export interface SessionExpiredEvent {
+  type: 'SessionExpired'
+  id: string
+  aumid: string
+}
+export interface SessionRevivedEvent {
+  type: 'SessionRevived'
+  id: string
+  aumid: string
+}
+export interface ActiveSessionChangedEvent {
+  type: 'ActiveSessionChanged'
+  id?: string
+  aumid?: string
+}
+export interface SystemSessionChangedEvent {
+  type: 'SystemSessionChanged'
+  aumid?: string
+}
+export type PlayerEvent = PlaybackInfoChangedEvent | MediaPropertiesChangedEvent | TimelinePropertiesChangedEvent | TrackChangedEvent | SeekedEvent | PositionTickEvent | StatusChangedEvent
+export type ManagerEvent = SessionsChangedEvent | SessionAddedEvent | SessionRemovedEvent | SessionExpiredEvent | SessionRevivedEvent | ActiveSessionChangedEvent | SystemSessionChangedEvent
+export interface DebounceOptions {
+  playbackInfo?: number
+  mediaProperties?: number
+  timelineProperties?: number
+}
+export interface SessionFilter {
+  allow?: Array<string>
+  deny?: Array<string>
+  allowNames?: Array<string>
+  denyNames?: Array<string>
+}
+export interface ArtistSplitting {
+  separators?: Array<string>
+  protectedNames?: Array<string>
+}
+export interface Normalization {
+  rulesFile?: string
+}
+export interface PlayerManagerOptions {
+  eventQueueCapacity?: number
+  overflowPolicy?: 'DropOldest' | 'Coalesce' | 'Block'
+  debounce?: DebounceOptions
+  seekThreshold?: number
+  positionInterval?: number
+  autoSync?: boolean
+  denylist?: Array<string>
+  filter?: SessionFilter
+  idleTimeout?: number
+  artistSplitting?: ArtistSplitting
+  normalization?: Normalization
+}
+export function getPlayerManager(options?: PlayerManagerOptions | undefined | null): Promise<PlayerManager | null>
 export function getFriendlyNameFor(aumid: string): Promise<string | null>
 export type JsPlayer = Player
 export class Player {
-  constructor(player: ExternalObject<Player>)
-  pollNextEvent(): Promise<string>
+  constructor(player: ExternalObject<Player>, subscriber?: ExternalObject<PlayerSubscriber> | undefined | null)
+  pollNextEvent(): Promise<PlayerEvent | null>
+  getDroppedEvents(): Promise<number>
+  subscribe(callback: (event: PlayerEvent) => void): Subscription
   getStatus(): Promise<Status>
   getAumid(): Promise<string>
   play(): Promise<boolean>
@@ -69,14 +187,31 @@ export class Player {
   setPosition(positionS: number): Promise<boolean>
   getPosition(wantsCurrentPosition: boolean): Promise<Position | null>
 }
+export type JsSubscription = Subscription
+export class Subscription {
+  constructor(task: ExternalObject<JoinHandle>)
+  unsubscribe(): void
+}
 export type JsPlayerManager = PlayerManager
 export class PlayerManager {
   constructor(playerManager: ExternalObject<PlayerManager>)
-  pollNextEvent(): Promise<string>
+  pollNextEvent(): Promise<ManagerEvent | null>
+  getDroppedEvents(): number
+  subscribe(callback: (event: ManagerEvent) => void): Subscription
   getActiveSession(): Player | null
-  getSession(aumid: string): Player | null
+  getActiveSessionId(): string | null
+  getSession(id: string): Player | null
+  getSessionIdsFor(aumid: string): Array<string>
+  getSessionsFor(aumid: string): Array<Player>
   getSessionsKeys(): Array<string>
+  getSessionsByRecency(): Array<SessionActivity>
+  getAllStatuses(withArt?: boolean | undefined | null): Promise<Record<string, Status>>
   getSystemSession(): Player | null
   updateSystemSession(): void
+  pinSession(aumid: string): void
+  unpinSession(): void
+  getPinnedSession(): string | null
+  setSelectionPolicy(policy: 'Default' | 'MostRecentlyPlaying' | 'IgnoreSystemSession' | 'StickyUntilStopped' | 'PriorityList', priority?: Array<string>): void
   updateSessions(denylist?: Array<string> | undefined | null): void
+  updateSessionsWith(filter: SessionFilter): void
 }
@@ -1,36 +1,76 @@
 use std::sync::Arc;
 
-use napi::bindgen_prelude::External;
+use napi::{
+    bindgen_prelude::{spawn, External},
+    threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode},
+    JsFunction, Result,
+};
 use napi_derive::napi;
 use tokio::sync::Mutex;
 
+use winplayer_core::{Player, PlayerSubscriber};
+
 use crate::{
-    jstypes::{JsPosition, JsStatus},
-    owo::player::{Player, PlayerEvent},
+    jssubscription::JsSubscription,
+    jstypes::{js_player_event, JsPlayerEvent, JsPosition, JsStatus},
+    Session,
 };
 
 #[napi(js_name = "Player")]
 pub struct JsPlayer {
-    player: External<Arc<Mutex<Player>>>,
+    player: External<Arc<Mutex<Player<Session>>>>,
+    subscriber: Option<External<PlayerSubscriber>>,
 }
 
 #[napi]
 impl JsPlayer {
     #[napi(constructor)]
-    pub fn new(player: External<Arc<Mutex<Player>>>) -> Self {
-        JsPlayer { player }
-    }
-
-    #[napi]
-    pub async unsafe fn poll_next_event(&mut self) -> String {
-        match self.player.lock().await.poll_next_event().await {
-            Some(PlayerEvent::PlaybackInfoChanged) => String::from("PlaybackInfoChanged"),
-            Some(PlayerEvent::MediaPropertiesChanged) => String::from("MediaPropertiesChanged"),
-            Some(PlayerEvent::TimelinePropertiesChanged) => {
-                String::from("TimelinePropertiesChanged")
+    pub fn new(
+        player: External<Arc<Mutex<Player<Session>>>>,
+        subscriber: Option<External<PlayerSubscriber>>,
+    ) -> Self {
+        JsPlayer { player, subscriber }
+    }
+
+    #[napi(ts_return_type = "Promise<PlayerEvent | null>")]
+    pub async fn poll_next_event(&self) -> Option<JsPlayerEvent> {
+        // only hold the player long enough to get the receiver, commands and
+        // getStatus() must not wait for the next event
+        let events = self.player.lock().await.get_event_receiver();
+        let event = events.recv().await?;
+        Some(js_player_event(event))
+    }
+
+    #[napi]
+    pub async fn get_dropped_events(&self) -> i64 {
+        self.player.lock().await.get_dropped_events() as i64
+    }
+
+    #[napi(ts_args_type = "callback: (event: PlayerEvent) => void")]
+    pub fn subscribe(&self, callback: JsFunction) -> Result<JsSubscription> {
+        let tsfn: ThreadsafeFunction<JsPlayerEvent, ErrorStrategy::Fatal> =
+            callback.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+
+        // subscribed before returning, or the events fired until the task
+        // gets to run would be lost; without the subscriber, that is for a
+        // session no longer tracked, the task waits for the player instead of
+        // this thread
+        let rx = self.subscriber.as_ref().map(|x| x.subscribe());
+        let player = self.player.clone();
+        let task = spawn(async move {
+            let rx = match rx {
+                Some(rx) => rx,
+                None => player.lock().await.subscribe(),
+            };
+            while let Some(event) = rx.recv().await {
+                tsfn.call(
+                    js_player_event(event),
+                    ThreadsafeFunctionCallMode::NonBlocking,
+                );
             }
-            None => String::from("None"),
-        }
+        });
+
+        Ok(JsSubscription::new(External::new(task)))
     }
 
     #[napi(ts_return_type = "Promise<Status>")]
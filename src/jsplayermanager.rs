@@ -1,60 +1,185 @@
-use napi::bindgen_prelude::External;
+use std::{collections::HashMap, sync::Arc};
+
+use napi::{
+    bindgen_prelude::{spawn, External},
+    threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode},
+    Error, JsFunction, Result, Status,
+};
 use napi_derive::napi;
+use tokio::sync::Mutex;
 
 use crate::jsplayer::JsPlayer;
-use crate::owo::playermanager::{ManagerEvent, PlayerManager};
+use crate::jssubscription::JsSubscription;
+use crate::jstypes::{
+    js_manager_event, JsManagerEvent, JsSessionActivity, JsSessionFilter, JsStatus,
+};
+use crate::{Backend, Session};
+use winplayer_core::{
+    selection::{
+        DefaultPolicy, IgnoreSystemSession, MostRecentlyPlaying, PriorityList, StickyUntilStopped,
+    },
+    Player, PlayerManager, SessionFilter,
+};
 
 #[napi(js_name = "PlayerManager")]
 pub struct JsPlayerManager {
-    player_manager: External<PlayerManager>,
+    player_manager: External<PlayerManager<Backend>>,
+}
+
+impl JsPlayerManager {
+    fn js_player(&self, player: Arc<Mutex<Player<Session>>>) -> JsPlayer {
+        let subscriber = self.player_manager.get_subscriber(&player);
+        JsPlayer::new(External::new(player), subscriber.map(External::new))
+    }
 }
 
 #[napi]
 impl JsPlayerManager {
     #[napi(constructor)]
-    pub fn new(player_manager: External<PlayerManager>) -> Self {
+    pub fn new(player_manager: External<PlayerManager<Backend>>) -> Self {
         JsPlayerManager { player_manager }
     }
 
+    #[napi(ts_return_type = "Promise<ManagerEvent | null>")]
+    pub async unsafe fn poll_next_event(&mut self) -> Option<JsManagerEvent> {
+        let event = self.player_manager.poll_next_event().await?;
+        Some(js_manager_event(event))
+    }
+
     #[napi]
-    pub async unsafe fn poll_next_event(&mut self) -> String {
-        match self.player_manager.poll_next_event().await {
-            Some(ManagerEvent::ActiveSessionChanged) => String::from("ActiveSessionChanged"),
-            Some(ManagerEvent::SystemSessionChanged) => String::from("SystemSessionChanged"),
-            Some(ManagerEvent::SessionsChanged) => String::from("SessionsChanged"),
-            None => String::from("None"),
-        }
+    pub fn get_dropped_events(&self) -> i64 {
+        self.player_manager.get_dropped_events() as i64
+    }
+
+    #[napi(ts_args_type = "callback: (event: ManagerEvent) => void")]
+    pub fn subscribe(&self, callback: JsFunction) -> Result<JsSubscription> {
+        let tsfn: ThreadsafeFunction<JsManagerEvent, ErrorStrategy::Fatal> =
+            callback.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+
+        let rx = self.player_manager.subscribe();
+        let task = spawn(async move {
+            while let Some(event) = rx.recv().await {
+                tsfn.call(
+                    js_manager_event(event),
+                    ThreadsafeFunctionCallMode::NonBlocking,
+                );
+            }
+        });
+
+        Ok(JsSubscription::new(External::new(task)))
     }
 
     #[napi]
     pub fn get_active_session(&self) -> Option<JsPlayer> {
         if let Some(player) = self.player_manager.get_active_session() {
-            return Some(JsPlayer::new(External::new(player)));
+            return Some(self.js_player(player));
         }
         None
     }
 
     #[napi]
-    pub fn get_session(&self, aumid: String) -> Option<JsPlayer> {
-        if let Some(player) = self.player_manager.get_session(&aumid) {
-            return Some(JsPlayer::new(External::new(player)));
+    pub fn get_active_session_id(&self) -> Option<String> {
+        self.player_manager.get_active_session_id()
+    }
+
+    #[napi]
+    pub fn get_session(&self, id: String) -> Option<JsPlayer> {
+        if let Some(player) = self.player_manager.get_session(&id) {
+            return Some(self.js_player(player));
         }
         None
     }
 
+    #[napi]
+    pub fn get_session_ids_for(&self, aumid: String) -> Vec<String> {
+        self.player_manager.get_session_ids_for(&aumid)
+    }
+
+    #[napi]
+    pub fn get_sessions_for(&self, aumid: String) -> Vec<JsPlayer> {
+        self.player_manager
+            .get_sessions_for(&aumid)
+            .into_iter()
+            .map(|player| self.js_player(player))
+            .collect()
+    }
+
     #[napi]
     pub fn get_sessions_keys(&self) -> Vec<String> {
         self.player_manager.get_sessions_keys()
     }
 
+    #[napi]
+    pub fn get_sessions_by_recency(&self) -> Vec<JsSessionActivity> {
+        self.player_manager
+            .get_sessions_by_recency()
+            .into_iter()
+            .map(JsSessionActivity::from)
+            .collect()
+    }
+
+    #[napi(ts_return_type = "Promise<Record<string, Status>>")]
+    pub async fn get_all_statuses(&self, with_art: Option<bool>) -> HashMap<String, JsStatus> {
+        self.player_manager
+            .get_all_statuses(with_art.unwrap_or(true))
+            .into_iter()
+            .map(|(id, status)| (id, JsStatus::from(status)))
+            .collect()
+    }
+
     #[napi]
     pub fn get_system_session(&self) -> Option<JsPlayer> {
         if let Some(player) = self.player_manager.get_system_session() {
-            return Some(JsPlayer::new(External::new(player)));
+            return Some(self.js_player(player));
         }
         None
     }
 
+    #[napi]
+    pub fn pin_session(&mut self, aumid: String) {
+        self.player_manager.pin_session(&aumid)
+    }
+
+    #[napi]
+    pub fn unpin_session(&mut self) {
+        self.player_manager.unpin_session()
+    }
+
+    #[napi]
+    pub fn get_pinned_session(&self) -> Option<String> {
+        self.player_manager.get_pinned_session()
+    }
+
+    #[napi(
+        ts_args_type = "policy: 'Default' | 'MostRecentlyPlaying' | 'IgnoreSystemSession' | 'StickyUntilStopped' | 'PriorityList', priority?: Array<string>"
+    )]
+    pub fn set_selection_policy(
+        &mut self,
+        policy: String,
+        priority: Option<Vec<String>>,
+    ) -> Result<()> {
+        match policy.as_str() {
+            "Default" => self.player_manager.set_selection_policy(DefaultPolicy),
+            "MostRecentlyPlaying" => self
+                .player_manager
+                .set_selection_policy(MostRecentlyPlaying::default()),
+            "IgnoreSystemSession" => self
+                .player_manager
+                .set_selection_policy(IgnoreSystemSession),
+            "StickyUntilStopped" => self.player_manager.set_selection_policy(StickyUntilStopped),
+            "PriorityList" => self.player_manager.set_selection_policy(PriorityList {
+                aumids: priority.unwrap_or_default(),
+            }),
+            _ => {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!("unknown selection policy {}", policy),
+                ))
+            }
+        }
+        Ok(())
+    }
+
     #[napi]
     pub fn update_system_session(&mut self) {
         self.player_manager.update_system_session()
@@ -64,4 +189,11 @@ impl JsPlayerManager {
     pub fn update_sessions(&mut self, denylist: Option<Vec<String>>) {
         self.player_manager.update_sessions(denylist.as_ref())
     }
+
+    #[napi]
+    pub fn update_sessions_with(&mut self, filter: JsSessionFilter) -> Result<()> {
+        let filter = SessionFilter::try_from(filter)?;
+        self.player_manager.update_sessions_with(&filter);
+        Ok(())
+    }
 }
@@ -0,0 +1,25 @@
+use napi::bindgen_prelude::External;
+use napi_derive::napi;
+use tokio::task::JoinHandle;
+
+// Not unsubscribing on drop: a subscription whose handle was thrown away is
+// still expected to deliver, like any other listener
+#[napi(js_name = "Subscription")]
+pub struct JsSubscription {
+    task: External<JoinHandle<()>>,
+}
+
+#[napi]
+impl JsSubscription {
+    #[napi(constructor)]
+    pub fn new(task: External<JoinHandle<()>>) -> Self {
+        JsSubscription { task }
+    }
+
+    #[napi]
+    pub fn unsubscribe(&self) {
+        // the task owns the receiver and the threadsafe function, aborting it
+        // releases both
+        self.task.abort();
+    }
+}
@@ -1,127 +1,594 @@
-use chrono::{DateTime, Utc};
-use napi::bindgen_prelude::Buffer;
-use napi_derive::napi;
-
-use crate::owo::types::{ArtData, Capabilities, Metadata, Position, Status};
-
-#[napi(object, js_name = "ArtData")]
-pub struct JsArtData {
-    pub data: Buffer,
-    pub mimetype: String,
-}
-
-impl From<ArtData> for JsArtData {
-    fn from(value: ArtData) -> Self {
-        JsArtData {
-            data: value.data.into(),
-            mimetype: value.mimetype,
-        }
-    }
-}
-
-#[napi(object, js_name = "Metadata")]
-pub struct JsMetadata {
-    pub album: Option<String>,
-    pub album_artist: Option<String>,
-    pub album_artists: Option<Vec<String>>,
-    pub artist: String,
-    pub artists: Vec<String>,
-    pub art_data: Option<JsArtData>,
-    pub id: Option<String>,
-    pub length: f64,
-    pub title: String,
-}
-
-impl From<Metadata> for JsMetadata {
-    fn from(value: Metadata) -> Self {
-        JsMetadata {
-            album: value.album,
-            album_artist: value.album_artist,
-            album_artists: value.album_artists,
-            artist: value.artist,
-            artists: value.artists,
-            art_data: 'rt: {
-                if let Some(art_data) = value.art_data {
-                    break 'rt Some(JsArtData::from(art_data));
-                };
-                None
-            },
-            id: value.id,
-            length: value.length,
-            title: value.title,
-        }
-    }
-}
-
-#[napi(object, js_name = "Capabilities")]
-pub struct JsCapabilities {
-    pub can_control: bool,
-    pub can_play_pause: bool,
-    pub can_go_next: bool,
-    pub can_go_previous: bool,
-    pub can_seek: bool,
-}
-
-impl From<Capabilities> for JsCapabilities {
-    fn from(value: Capabilities) -> Self {
-        JsCapabilities {
-            can_control: value.can_control,
-            can_play_pause: value.can_play_pause,
-            can_go_next: value.can_go_next,
-            can_go_previous: value.can_go_previous,
-            can_seek: value.can_seek,
-        }
-    }
-}
-
-#[napi(object, js_name = "Position")]
-pub struct JsPosition {
-    pub how_much: f64,
-    pub when: DateTime<Utc>,
-}
-
-impl From<Position> for JsPosition {
-    fn from(value: Position) -> Self {
-        JsPosition {
-            how_much: value.how_much,
-            when: value.when,
-        }
-    }
-}
-
-#[napi(object, js_name = "Status")]
-pub struct JsStatus {
-    pub metadata: Option<JsMetadata>,
-    pub capabilities: JsCapabilities,
-    pub status: String,
-    pub is_loop: String,
-    pub shuffle: bool,
-    pub volume: f64, // tanto sta a -1 lmao
-    pub elapsed: Option<JsPosition>,
-    pub app: Option<String>, // App User Model ID
-}
-
-impl From<Status> for JsStatus {
-    fn from(value: Status) -> Self {
-        JsStatus {
-            metadata: 'rt: {
-                if let Some(metadata) = value.metadata {
-                    break 'rt Some(JsMetadata::from(metadata));
-                };
-                None
-            },
-            capabilities: JsCapabilities::from(value.capabilities),
-            status: value.status,
-            is_loop: value.is_loop,
-            shuffle: value.shuffle,
-            volume: value.volume,
-            elapsed: 'rt: {
-                if let Some(elapsed) = value.elapsed {
-                    break 'rt Some(JsPosition::from(elapsed));
-                };
-                None
-            },
-            app: value.app,
-        }
-    }
-}
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use napi::{
+    bindgen_prelude::{Buffer, Either4, Either7},
+    Error, Status as NapiStatus,
+};
+use napi_derive::napi;
+
+use winplayer_core::{
+    ArtData, ArtistSplitting, Capabilities, DebounceOptions, FieldChange, FieldValue, ManagerEvent,
+    Metadata, NormalizationRules, OverflowPolicy, Pattern, PlayerEvent, PlayerManagerOptions,
+    Position, QueueOptions, RawMetadata, SessionActivity, SessionFilter, SessionMatcher, Status,
+};
+
+#[napi(object, js_name = "ArtData")]
+pub struct JsArtData {
+    pub data: Buffer,
+    pub mimetype: String,
+}
+
+impl From<ArtData> for JsArtData {
+    fn from(value: ArtData) -> Self {
+        JsArtData {
+            data: value.data.into(),
+            mimetype: value.mimetype,
+        }
+    }
+}
+
+#[napi(object, js_name = "Metadata")]
+pub struct JsMetadata {
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub album_artists: Option<Vec<String>>,
+    pub artist: String,
+    pub artists: Vec<String>,
+    pub art_data: Option<JsArtData>,
+    pub id: Option<String>,
+    pub length: f64,
+    pub title: String,
+    pub raw: Option<JsRawMetadata>,
+}
+
+impl From<Metadata> for JsMetadata {
+    fn from(value: Metadata) -> Self {
+        JsMetadata {
+            album: value.album,
+            album_artist: value.album_artist,
+            album_artists: value.album_artists,
+            artist: value.artist,
+            artists: value.artists,
+            art_data: 'rt: {
+                if let Some(art_data) = value.art_data {
+                    break 'rt Some(JsArtData::from(art_data));
+                };
+                None
+            },
+            id: value.id,
+            length: value.length,
+            title: value.title,
+            raw: value.raw.map(JsRawMetadata::from),
+        }
+    }
+}
+
+#[napi(object, js_name = "RawMetadata")]
+pub struct JsRawMetadata {
+    pub album_artist: Option<String>,
+    pub artist: String,
+    pub title: String,
+}
+
+impl From<RawMetadata> for JsRawMetadata {
+    fn from(value: RawMetadata) -> Self {
+        JsRawMetadata {
+            album_artist: value.album_artist,
+            artist: value.artist,
+            title: value.title,
+        }
+    }
+}
+
+#[napi(object, js_name = "Capabilities")]
+pub struct JsCapabilities {
+    pub can_control: bool,
+    pub can_play_pause: bool,
+    pub can_go_next: bool,
+    pub can_go_previous: bool,
+    pub can_seek: bool,
+}
+
+impl From<Capabilities> for JsCapabilities {
+    fn from(value: Capabilities) -> Self {
+        JsCapabilities {
+            can_control: value.can_control,
+            can_play_pause: value.can_play_pause,
+            can_go_next: value.can_go_next,
+            can_go_previous: value.can_go_previous,
+            can_seek: value.can_seek,
+        }
+    }
+}
+
+#[napi(object, js_name = "Position")]
+pub struct JsPosition {
+    pub how_much: f64,
+    pub when: DateTime<Utc>,
+}
+
+impl From<Position> for JsPosition {
+    fn from(value: Position) -> Self {
+        JsPosition {
+            how_much: value.how_much,
+            when: value.when,
+        }
+    }
+}
+
+#[napi(object, js_name = "SessionActivity")]
+pub struct JsSessionActivity {
+    pub id: String,
+    pub aumid: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_playing: Option<DateTime<Utc>>,
+    pub last_event: Option<DateTime<Utc>>,
+}
+
+impl From<SessionActivity> for JsSessionActivity {
+    fn from(value: SessionActivity) -> Self {
+        JsSessionActivity {
+            id: value.id,
+            aumid: value.aumid,
+            first_seen: value.first_seen,
+            last_playing: value.last_playing,
+            last_event: value.last_event,
+        }
+    }
+}
+
+#[napi(object, js_name = "Status")]
+pub struct JsStatus {
+    pub metadata: Option<JsMetadata>,
+    pub capabilities: JsCapabilities,
+    pub status: String,
+    pub is_loop: String,
+    pub shuffle: bool,
+    pub volume: f64, // tanto sta a -1 lmao
+    pub elapsed: Option<JsPosition>,
+    pub app: Option<String>, // App User Model ID
+}
+
+impl From<Status> for JsStatus {
+    fn from(value: Status) -> Self {
+        JsStatus {
+            metadata: 'rt: {
+                if let Some(metadata) = value.metadata {
+                    break 'rt Some(JsMetadata::from(metadata));
+                };
+                None
+            },
+            capabilities: JsCapabilities::from(value.capabilities),
+            status: value.status,
+            is_loop: value.is_loop,
+            shuffle: value.shuffle,
+            volume: value.volume,
+            elapsed: 'rt: {
+                if let Some(elapsed) = value.elapsed {
+                    break 'rt Some(JsPosition::from(elapsed));
+                };
+                None
+            },
+            app: value.app,
+        }
+    }
+}
+
+#[napi(object, js_name = "PlaybackInfoChangedEvent")]
+pub struct JsPlaybackInfoChangedEvent {
+    #[napi(js_name = "type", ts_type = "'PlaybackInfoChanged'")]
+    pub kind: String,
+    pub status: String,
+    pub is_loop: String,
+    pub shuffle: bool,
+    pub capabilities: JsCapabilities,
+}
+
+#[napi(object, js_name = "MediaPropertiesChangedEvent")]
+pub struct JsMediaPropertiesChangedEvent {
+    #[napi(js_name = "type", ts_type = "'MediaPropertiesChanged'")]
+    pub kind: String,
+    pub metadata: Option<JsMetadata>,
+}
+
+#[napi(object, js_name = "TimelinePropertiesChangedEvent")]
+pub struct JsTimelinePropertiesChangedEvent {
+    #[napi(js_name = "type", ts_type = "'TimelinePropertiesChanged'")]
+    pub kind: String,
+    pub position: Option<JsPosition>,
+}
+
+#[napi(object, js_name = "TrackChangedEvent")]
+pub struct JsTrackChangedEvent {
+    #[napi(js_name = "type", ts_type = "'TrackChanged'")]
+    pub kind: String,
+    pub previous: Option<JsMetadata>,
+    pub current: JsMetadata,
+}
+
+pub type JsFieldValue = Either4<bool, f64, String, Vec<String>>;
+
+fn js_field_value(value: FieldValue) -> JsFieldValue {
+    match value {
+        FieldValue::Bool(x) => Either4::A(x),
+        FieldValue::Number(x) => Either4::B(x),
+        FieldValue::Text(x) => Either4::C(x),
+        FieldValue::List(x) => Either4::D(x),
+    }
+}
+
+// metadata.album_artist -> metadata.albumArtist, like the fields themselves
+fn js_field_name(field: &str) -> String {
+    let mut name = String::with_capacity(field.len());
+    let mut upper = false;
+    for c in field.chars() {
+        match c {
+            '_' => upper = true,
+            c if upper => {
+                name.extend(c.to_uppercase());
+                upper = false;
+            }
+            c => name.push(c),
+        }
+    }
+    name
+}
+
+#[napi(object, js_name = "FieldChange")]
+pub struct JsFieldChange {
+    pub field: String,
+    pub old: Option<JsFieldValue>,
+    pub new: Option<JsFieldValue>,
+}
+
+impl From<FieldChange> for JsFieldChange {
+    fn from(value: FieldChange) -> Self {
+        JsFieldChange {
+            field: js_field_name(value.field),
+            old: value.old.map(js_field_value),
+            new: value.new.map(js_field_value),
+        }
+    }
+}
+
+#[napi(object, js_name = "SeekedEvent")]
+pub struct JsSeekedEvent {
+    #[napi(js_name = "type", ts_type = "'Seeked'")]
+    pub kind: String,
+    pub from: f64,
+    pub to: f64,
+}
+
+#[napi(object, js_name = "PositionTickEvent")]
+pub struct JsPositionTickEvent {
+    #[napi(js_name = "type", ts_type = "'PositionTick'")]
+    pub kind: String,
+    pub position: JsPosition,
+}
+
+#[napi(object, js_name = "StatusChangedEvent")]
+pub struct JsStatusChangedEvent {
+    #[napi(js_name = "type", ts_type = "'StatusChanged'")]
+    pub kind: String,
+    pub changes: Vec<JsFieldChange>,
+}
+
+pub type JsPlayerEvent = Either7<
+    JsPlaybackInfoChangedEvent,
+    JsMediaPropertiesChangedEvent,
+    JsTimelinePropertiesChangedEvent,
+    JsTrackChangedEvent,
+    JsSeekedEvent,
+    JsPositionTickEvent,
+    JsStatusChangedEvent,
+>;
+
+// Either7 is not ours, so no From impl
+pub fn js_player_event(value: PlayerEvent) -> JsPlayerEvent {
+    match value {
+        PlayerEvent::PlaybackInfoChanged {
+            status,
+            is_loop,
+            shuffle,
+            capabilities,
+        } => Either7::A(JsPlaybackInfoChangedEvent {
+            kind: String::from("PlaybackInfoChanged"),
+            status,
+            is_loop,
+            shuffle,
+            capabilities: JsCapabilities::from(capabilities),
+        }),
+        PlayerEvent::MediaPropertiesChanged { metadata } => {
+            Either7::B(JsMediaPropertiesChangedEvent {
+                kind: String::from("MediaPropertiesChanged"),
+                metadata: metadata.map(JsMetadata::from),
+            })
+        }
+        PlayerEvent::TimelinePropertiesChanged { position } => {
+            Either7::C(JsTimelinePropertiesChangedEvent {
+                kind: String::from("TimelinePropertiesChanged"),
+                position: position.map(JsPosition::from),
+            })
+        }
+        PlayerEvent::TrackChanged { previous, current } => Either7::D(JsTrackChangedEvent {
+            kind: String::from("TrackChanged"),
+            previous: previous.map(JsMetadata::from),
+            current: JsMetadata::from(current),
+        }),
+        PlayerEvent::Seeked { from, to } => Either7::E(JsSeekedEvent {
+            kind: String::from("Seeked"),
+            from,
+            to,
+        }),
+        PlayerEvent::PositionTick { position } => Either7::F(JsPositionTickEvent {
+            kind: String::from("PositionTick"),
+            position: JsPosition::from(position),
+        }),
+        PlayerEvent::StatusChanged { changes } => Either7::G(JsStatusChangedEvent {
+            kind: String::from("StatusChanged"),
+            changes: changes.into_iter().map(JsFieldChange::from).collect(),
+        }),
+    }
+}
+
+#[napi(object, js_name = "SessionsChangedEvent")]
+pub struct JsSessionsChangedEvent {
+    #[napi(js_name = "type", ts_type = "'SessionsChanged'")]
+    pub kind: String,
+}
+
+#[napi(object, js_name = "SessionAddedEvent")]
+pub struct JsSessionAddedEvent {
+    #[napi(js_name = "type", ts_type = "'SessionAdded'")]
+    pub kind: String,
+    pub id: String,
+    pub aumid: String,
+}
+
+#[napi(object, js_name = "SessionRemovedEvent")]
+pub struct JsSessionRemovedEvent {
+    #[napi(js_name = "type", ts_type = "'SessionRemoved'")]
+    pub kind: String,
+    pub id: String,
+    pub aumid: String,
+}
+
+#[napi(object, js_name = "SessionExpiredEvent")]
+pub struct JsSessionExpiredEvent {
+    #[napi(js_name = "type", ts_type = "'SessionExpired'")]
+    pub kind: String,
+    pub id: String,
+    pub aumid: String,
+}
+
+#[napi(object, js_name = "SessionRevivedEvent")]
+pub struct JsSessionRevivedEvent {
+    #[napi(js_name = "type", ts_type = "'SessionRevived'")]
+    pub kind: String,
+    pub id: String,
+    pub aumid: String,
+}
+
+#[napi(object, js_name = "ActiveSessionChangedEvent")]
+pub struct JsActiveSessionChangedEvent {
+    #[napi(js_name = "type", ts_type = "'ActiveSessionChanged'")]
+    pub kind: String,
+    pub id: Option<String>,
+    pub aumid: Option<String>,
+}
+
+#[napi(object, js_name = "SystemSessionChangedEvent")]
+pub struct JsSystemSessionChangedEvent {
+    #[napi(js_name = "type", ts_type = "'SystemSessionChanged'")]
+    pub kind: String,
+    pub aumid: Option<String>,
+}
+
+pub type JsManagerEvent = Either7<
+    JsSessionsChangedEvent,
+    JsSessionAddedEvent,
+    JsSessionRemovedEvent,
+    JsSessionExpiredEvent,
+    JsSessionRevivedEvent,
+    JsActiveSessionChangedEvent,
+    JsSystemSessionChangedEvent,
+>;
+
+pub fn js_manager_event(value: ManagerEvent) -> JsManagerEvent {
+    match value {
+        ManagerEvent::SessionsChanged => Either7::A(JsSessionsChangedEvent {
+            kind: String::from("SessionsChanged"),
+        }),
+        ManagerEvent::SessionAdded { id, aumid } => Either7::B(JsSessionAddedEvent {
+            kind: String::from("SessionAdded"),
+            id,
+            aumid,
+        }),
+        ManagerEvent::SessionRemoved { id, aumid } => Either7::C(JsSessionRemovedEvent {
+            kind: String::from("SessionRemoved"),
+            id,
+            aumid,
+        }),
+        ManagerEvent::SessionExpired { id, aumid } => Either7::D(JsSessionExpiredEvent {
+            kind: String::from("SessionExpired"),
+            id,
+            aumid,
+        }),
+        ManagerEvent::SessionRevived { id, aumid } => Either7::E(JsSessionRevivedEvent {
+            kind: String::from("SessionRevived"),
+            id,
+            aumid,
+        }),
+        ManagerEvent::ActiveSessionChanged { id, aumid } => {
+            Either7::F(JsActiveSessionChangedEvent {
+                kind: String::from("ActiveSessionChanged"),
+                id,
+                aumid,
+            })
+        }
+        ManagerEvent::SystemSessionChanged { aumid } => Either7::G(JsSystemSessionChangedEvent {
+            kind: String::from("SystemSessionChanged"),
+            aumid,
+        }),
+    }
+}
+
+// milliseconds
+#[napi(object, js_name = "DebounceOptions")]
+pub struct JsDebounceOptions {
+    pub playback_info: Option<u32>,
+    pub media_properties: Option<u32>,
+    pub timeline_properties: Option<u32>,
+}
+
+impl From<JsDebounceOptions> for DebounceOptions {
+    fn from(value: JsDebounceOptions) -> Self {
+        let window = |ms: Option<u32>| ms.map(|x| Duration::from_millis(x as u64));
+        DebounceOptions {
+            playback_info: window(value.playback_info),
+            media_properties: window(value.media_properties),
+            timeline_properties: window(value.timeline_properties),
+        }
+    }
+}
+
+// patterns in their string form, see Pattern::parse
+#[napi(object, js_name = "SessionFilter")]
+pub struct JsSessionFilter {
+    pub allow: Option<Vec<String>>,
+    pub deny: Option<Vec<String>>,
+    pub allow_names: Option<Vec<String>>,
+    pub deny_names: Option<Vec<String>>,
+}
+
+impl TryFrom<JsSessionFilter> for SessionFilter {
+    type Error = Error;
+
+    fn try_from(value: JsSessionFilter) -> Result<Self, Self::Error> {
+        let matchers = |aumids: Option<Vec<String>>, names: Option<Vec<String>>| {
+            let parse = |x: String| {
+                Pattern::parse(&x)
+                    .map_err(|e| Error::new(NapiStatus::InvalidArg, format!("{}: {}", x, e)))
+            };
+            let aumids = aumids
+                .into_iter()
+                .flatten()
+                .map(|x| parse(x).map(SessionMatcher::Aumid));
+            let names = names
+                .into_iter()
+                .flatten()
+                .map(|x| parse(x).map(SessionMatcher::FriendlyName));
+            aumids.chain(names).collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(SessionFilter {
+            allow: matchers(value.allow, value.allow_names)?,
+            deny: matchers(value.deny, value.deny_names)?,
+        })
+    }
+}
+
+#[napi(object, js_name = "ArtistSplitting")]
+pub struct JsArtistSplitting {
+    pub separators: Option<Vec<String>>,
+    pub protected_names: Option<Vec<String>>,
+}
+
+impl From<JsArtistSplitting> for ArtistSplitting {
+    fn from(value: JsArtistSplitting) -> Self {
+        let common = ArtistSplitting::common();
+        ArtistSplitting {
+            separators: value.separators.unwrap_or(common.separators),
+            protected: value.protected_names.unwrap_or_default(),
+        }
+    }
+}
+
+#[napi(object, js_name = "Normalization")]
+pub struct JsNormalization {
+    pub rules_file: Option<String>,
+}
+
+impl TryFrom<JsNormalization> for NormalizationRules {
+    type Error = Error;
+
+    fn try_from(value: JsNormalization) -> Result<Self, Self::Error> {
+        match value.rules_file {
+            Some(path) => NormalizationRules::from_file(&path)
+                .map_err(|x| Error::new(NapiStatus::InvalidArg, format!("{}: {}", path, x))),
+            None => Ok(NormalizationRules::common()),
+        }
+    }
+}
+
+#[napi(object, js_name = "PlayerManagerOptions")]
+pub struct JsPlayerManagerOptions {
+    pub event_queue_capacity: Option<u32>,
+    #[napi(ts_type = "'DropOldest' | 'Coalesce' | 'Block'")]
+    pub overflow_policy: Option<String>,
+    pub debounce: Option<JsDebounceOptions>,
+    // milliseconds
+    pub seek_threshold: Option<u32>,
+    pub position_interval: Option<u32>,
+    pub auto_sync: Option<bool>,
+    pub denylist: Option<Vec<String>>,
+    pub filter: Option<JsSessionFilter>,
+    // milliseconds
+    pub idle_timeout: Option<u32>,
+    pub artist_splitting: Option<JsArtistSplitting>,
+    pub normalization: Option<JsNormalization>,
+}
+
+impl TryFrom<JsPlayerManagerOptions> for PlayerManagerOptions {
+    type Error = Error;
+
+    fn try_from(value: JsPlayerManagerOptions) -> Result<Self, Self::Error> {
+        let mut options = PlayerManagerOptions::default();
+
+        let event_queue = QueueOptions {
+            capacity: value
+                .event_queue_capacity
+                .map_or(options.event_queue.capacity, |x| x as usize),
+            policy: match value.overflow_policy.as_deref() {
+                None | Some("DropOldest") => OverflowPolicy::DropOldest,
+                Some("Coalesce") => OverflowPolicy::Coalesce,
+                Some("Block") => OverflowPolicy::Block,
+                Some(policy) => {
+                    return Err(Error::new(
+                        NapiStatus::InvalidArg,
+                        format!("unknown overflow policy {}", policy),
+                    ))
+                }
+            },
+        };
+        options.event_queue = event_queue;
+        options.player.event_queue = event_queue;
+        if let Some(debounce) = value.debounce {
+            options.player.debounce = DebounceOptions::from(debounce);
+        }
+        if let Some(seek_threshold) = value.seek_threshold {
+            options.player.seek_threshold = Duration::from_millis(seek_threshold as u64);
+        }
+        options.player.position_interval = value
+            .position_interval
+            .map(|x| Duration::from_millis(x as u64));
+        options.auto_sync = value.auto_sync.unwrap_or_default();
+        if let Some(filter) = value.filter {
+            options.filter = SessionFilter::try_from(filter)?;
+        }
+        // the plain denylist comes on top of the filter
+        let denylist = SessionFilter::denylist(&value.denylist.unwrap_or_default());
+        options.filter.deny.extend(denylist.deny);
+        options.idle_timeout = value.idle_timeout.map(|x| Duration::from_millis(x as u64));
+        if let Some(artist_splitting) = value.artist_splitting {
+            options.player.artist_splitting = ArtistSplitting::from(artist_splitting);
+        }
+        options.player.normalization = value
+            .normalization
+            .map(NormalizationRules::try_from)
+            .transpose()?;
+
+        Ok(options)
+    }
+}
@@ -1,23 +1,41 @@
 use jsplayermanager::JsPlayerManager;
-use napi::bindgen_prelude::External;
+use napi::{bindgen_prelude::External, Result};
 use napi_derive::napi;
-use owo::{playermanager::PlayerManager, util::get_session_player_name};
+use winplayer_core::{backend::MediaSessionManager, PlayerManager, PlayerManagerOptions};
 
-mod owo;
+use crate::jstypes::JsPlayerManagerOptions;
 
 mod jsplayer;
 mod jsplayermanager;
+mod jssubscription;
 mod jstypes;
 
+#[cfg(not(any(all(feature = "winrt", windows), feature = "mpris")))]
+compile_error!("no media backend selected, enable the \"winrt\" or \"mpris\" feature");
+
+#[cfg(all(feature = "winrt", windows))]
+type Backend = winplayer_core::backend::winrt::GlobalSystemMediaTransportControlsSessionManager;
+
+#[cfg(all(feature = "mpris", not(all(feature = "winrt", windows))))]
+type Backend = winplayer_core::backend::mpris::MprisSessionManager;
+
+type Session = <Backend as MediaSessionManager>::Session;
+
 #[napi]
-pub async fn get_player_manager() -> Option<JsPlayerManager> {
-    if let Some(player_manager) = PlayerManager::new().await {
-        return Some(JsPlayerManager::new(External::new(player_manager)));
+pub async fn get_player_manager(
+    options: Option<JsPlayerManagerOptions>,
+) -> Result<Option<JsPlayerManager>> {
+    let options = match options {
+        Some(options) => PlayerManagerOptions::try_from(options)?,
+        None => PlayerManagerOptions::default(),
+    };
+    if let Some(player_manager) = PlayerManager::<Backend>::with_options(options).await {
+        return Ok(Some(JsPlayerManager::new(External::new(player_manager))));
     }
-    None
+    Ok(None)
 }
 
 #[napi]
 pub async fn get_friendly_name_for(aumid: String) -> Option<String> {
-    get_session_player_name(&aumid).await
+    Backend::request().await?.get_friendly_name(&aumid).await
 }
@@ -1,4 +0,0 @@
-pub(crate) mod player;
-pub(crate) mod playermanager;
-pub(crate) mod types;
-pub(crate) mod util;
@@ -18,20 +18,55 @@ async function main() {
 			console.log("tracked sessions:", keys);
 		});
 
+		playerManager.on("SessionAdded", (aumid, id) => {
+			console.log("manager event: SessionAdded");
+			console.log("aumid:", aumid, "id:", id);
+		});
+
+		playerManager.on("SessionRemoved", (aumid, id) => {
+			console.log("manager event: SessionRemoved");
+			console.log("aumid:", aumid, "id:", id);
+		});
+
+		playerManager.on("SessionExpired", (aumid, id) => {
+			console.log("manager event: SessionExpired");
+			console.log("aumid:", aumid, "id:", id);
+		});
+
+		playerManager.on("SessionRevived", (aumid, id) => {
+			console.log("manager event: SessionRevived");
+			console.log("aumid:", aumid, "id:", id);
+		});
+
 		playerManager.on("MediaPropertiesChanged", (status) => {
 			console.log("player event: MediaPropertiesChanged");
 			console.log("status:", status);
 		});
 
-		playerManager.on("PlaybackInfoChanged", (status) => {
+		playerManager.on("PlaybackInfoChanged", (status, evt) => {
 			console.log("player event: PlaybackInfoChanged");
-			console.log("status:", status);
+			console.log("status:", evt.status, "loop:", evt.isLoop, "shuffle:", evt.shuffle);
 		});
 
 		playerManager.on("TimelinePropertiesChanged", (position) => {
 			console.log("player event: TimelinePropertiesChanged");
 			console.log("position:", position);
 		});
+
+		playerManager.on("TrackChanged", (evt) => {
+			console.log("player event: TrackChanged");
+			console.log("from:", evt.previous?.title, "to:", evt.current.title);
+		});
+
+		playerManager.on("Seeked", (evt) => {
+			console.log("player event: Seeked");
+			console.log("from:", evt.from, "to:", evt.to);
+		});
+
+		playerManager.on("StatusChanged", (evt) => {
+			console.log("player event: StatusChanged");
+			for (const { field, old, new: now } of evt.changes) console.log(field + ":", old, "->", now);
+		});
 	} else {
 		console.error("whoops try again");
 		process.exit(1);
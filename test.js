@@ -7,7 +7,7 @@ async function main() {
 	async function pmEventPolling() {
 		while (evt = await playerManager.pollNextEvent()) {
 			console.log("manager event", evt);
-			switch (evt) {
+			switch (evt.type) {
 				case "SystemSessionChanged":
 					playerManager.updateSystemSession();
 					break;
@@ -30,14 +30,6 @@ async function main() {
 	async function eventPolling() {
 		while (evt = await player?.pollNextEvent()) {
 			console.log("player event", evt);
-			switch (evt) {
-				case "PlaybackInfoChanged":
-					console.log("status", await player?.getStatus())
-					break;
-				case "TimelinePropertiesChanged":
-					console.log("status and pos", await player?.getStatus(), await player?.getPosition(false))
-					break;
-			}
 		}
 	}
 